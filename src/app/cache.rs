@@ -0,0 +1,322 @@
+use crate::config::ThumbnailsConfig;
+
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, CACHE_CONTROL, ETAG, EXPIRES, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use tokio::task::spawn_blocking;
+
+/// Freshness of a response without caching headers.
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Eviction frees space down to this share of the size limit, to not evict on every store.
+const EVICTION_RATIO: f64 = 0.9;
+
+/// Validity and validators of a cached response.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct Meta {
+    pub url: String,
+    /// Seconds since the epoch until which the content can be used without revalidation
+    pub expires: u64,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+pub struct Entry {
+    pub content: Vec<u8>,
+    pub meta: Meta,
+}
+
+impl Entry {
+    pub fn is_fresh(&self) -> bool {
+        now() < self.meta.expires
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Responses stored in `$XDG_CACHE_HOME/ytui/thumbnails`, keyed by a hash of their URL.
+///
+/// The least recently used entries are removed once the size limit is reached.
+/// The files are accessed on the blocking threads of the runtime.
+pub struct Cache {
+    dir: Option<PathBuf>,
+    limit: u64,
+    /// Total size of the entries, computed on the first store
+    size: Mutex<Option<u64>>,
+}
+
+impl Cache {
+    pub fn new(config: &ThumbnailsConfig) -> Self {
+        let limit = config.cache_size * 1024 * 1024;
+        Self {
+            dir: dirs::cache_dir()
+                .map(|d| d.join("ytui").join("thumbnails"))
+                .filter(|_| limit > 0),
+            limit,
+            size: Mutex::new(None),
+        }
+    }
+
+    /// FNV-1a hash of the URL, stable across runs.
+    fn key(url: &str) -> String {
+        let hash = url.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x100000001b3)
+        });
+        format!("{hash:016x}")
+    }
+
+    fn paths(&self, url: &str) -> Option<(PathBuf, PathBuf)> {
+        let dir = self.dir.as_ref()?;
+        let key = Self::key(url);
+        Some((dir.join(format!("{key}.bin")), dir.join(format!("{key}.json"))))
+    }
+
+    /// Cached response for `url`, fresh or not.
+    pub async fn get(self: &Arc<Self>, url: &str) -> Option<Entry> {
+        let (cache, url) = (self.clone(), url.to_string());
+        spawn_blocking(move || cache.read(&url)).await.ok().flatten()
+    }
+
+    fn read(&self, url: &str) -> Option<Entry> {
+        let (content_path, meta_path) = self.paths(url)?;
+        let meta: Meta = serde_json::from_slice(&fs::read(&meta_path).ok()?).ok()?;
+        if meta.url != url {
+            return None;
+        }
+        let content = fs::read(&content_path).ok()?;
+
+        // The modification time orders the entries by last use
+        if let Ok(file) = File::options().write(true).open(&content_path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+
+        Some(Entry { content, meta })
+    }
+
+    /// Validity of a response according to its caching headers, `None` if it must not be stored.
+    pub fn meta(url: &str, headers: &HeaderMap) -> Option<Meta> {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok()).map(String::from);
+
+        let mut max_age = None;
+        if let Some(cache_control) = header(CACHE_CONTROL) {
+            for directive in cache_control.split(',').map(|d| d.trim().to_lowercase()) {
+                match directive.split_once('=') {
+                    None if directive == "no-store" => return None,
+                    None if directive == "no-cache" => max_age = Some(0),
+                    Some(("max-age", seconds)) => max_age = seconds.trim_matches('"').parse().ok().or(max_age),
+                    _ => {}
+                }
+            }
+        }
+
+        let expires = match max_age {
+            Some(max_age) => now() + max_age,
+            None => header(EXPIRES)
+                .and_then(|expires| httpdate::parse_http_date(&expires).ok())
+                .and_then(|expires| expires.duration_since(UNIX_EPOCH).ok())
+                .map_or(now() + DEFAULT_MAX_AGE.as_secs(), |expires| expires.as_secs()),
+        };
+
+        Some(Meta {
+            url: url.to_string(),
+            expires,
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        })
+    }
+
+    /// Store a response, evicting the least recently used entries if the cache gets too big.
+    pub async fn put(self: &Arc<Self>, meta: Meta, content: Vec<u8>) {
+        let cache = self.clone();
+        let _ = spawn_blocking(move || cache.store(&meta, &content)).await;
+    }
+
+    fn store(&self, meta: &Meta, content: &[u8]) {
+        let Some((content_path, meta_path)) = self.paths(&meta.url) else {
+            return;
+        };
+        let Ok(json) = serde_json::to_vec(meta) else {
+            return;
+        };
+
+        if let Some(dir) = content_path.parent() {
+            if fs::create_dir_all(dir).is_err() {
+                return;
+            }
+        }
+        // An entry being replaced no longer counts
+        let len = |path| fs::metadata(path).map_or(0, |m| m.len());
+        let replaced = len(&content_path) + len(&meta_path);
+        if fs::write(&content_path, content).is_err() || fs::write(&meta_path, &json).is_err() {
+            return;
+        }
+
+        let mut size = self.size.lock().unwrap();
+        let total = match *size {
+            Some(total) => (total + (content.len() + json.len()) as u64).saturating_sub(replaced),
+            None => self.scan().iter().map(|(_, _, len)| len).sum(),
+        };
+        *size = Some(match total > self.limit {
+            true => self.evict(),
+            false => total,
+        });
+    }
+
+    /// Update the validity of an entry revalidated by the server.
+    pub async fn refresh(&self, meta: &Meta) {
+        if let (Some((_, meta_path)), Ok(json)) = (self.paths(&meta.url), serde_json::to_vec(meta)) {
+            let _ = spawn_blocking(move || fs::write(meta_path, json)).await;
+        }
+    }
+
+    /// Content files with their last use and the size of the entry.
+    fn scan(&self) -> Vec<(PathBuf, SystemTime, u64)> {
+        let Some(Ok(entries)) = self.dir.as_ref().map(fs::read_dir) else {
+            return vec![];
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "bin"))
+            .filter_map(|path| {
+                let metadata = fs::metadata(&path).ok()?;
+                let meta_len = fs::metadata(path.with_extension("json")).map_or(0, |m| m.len());
+                Some((path, metadata.modified().ok()?, metadata.len() + meta_len))
+            })
+            .collect()
+    }
+
+    /// Remove the least recently used entries, returning the remaining size.
+    fn evict(&self) -> u64 {
+        let mut entries = self.scan();
+        entries.sort_by_key(|(_, used, _)| *used);
+
+        let target = (self.limit as f64 * EVICTION_RATIO) as u64;
+        let mut total: u64 = entries.iter().map(|(_, _, len)| len).sum();
+        for (path, _, len) in entries {
+            if total <= target {
+                break;
+            }
+            let _ = fs::remove_file(path.with_extension("json"));
+            if fs::remove_file(&path).is_ok() {
+                total -= len;
+            }
+        }
+
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::process;
+
+    fn headers(headers: &[(&'static str, &str)]) -> HeaderMap {
+        headers
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn meta_is_not_stored_with_no_store() {
+        let headers = headers(&[("cache-control", "public, no-store"), ("etag", "\"a\"")]);
+        assert!(Cache::meta("/vi/a.jpg", &headers).is_none());
+    }
+
+    #[test]
+    fn meta_with_no_cache_expires_now() {
+        let headers = headers(&[("cache-control", "no-cache"), ("etag", "\"a\"")]);
+        let meta = Cache::meta("/vi/a.jpg", &headers).unwrap();
+
+        assert!(meta.expires <= now());
+        assert_eq!(meta.etag.as_deref(), Some("\"a\""));
+    }
+
+    #[test]
+    fn meta_follows_max_age_over_expires() {
+        let headers = headers(&[
+            ("cache-control", "public, max-age=3600"),
+            ("expires", "Thu, 01 Jan 1970 00:00:00 GMT"),
+        ]);
+        let meta = Cache::meta("/vi/a.jpg", &headers).unwrap();
+
+        assert!((now() + 3599..=now() + 3600).contains(&meta.expires));
+    }
+
+    #[test]
+    fn meta_follows_expires() {
+        let expires = SystemTime::now() + Duration::from_secs(600);
+        let headers = headers(&[("expires", &httpdate::fmt_http_date(expires))]);
+        let meta = Cache::meta("/vi/a.jpg", &headers).unwrap();
+
+        assert_eq!(meta.expires, expires.duration_since(UNIX_EPOCH).unwrap().as_secs());
+    }
+
+    #[test]
+    fn meta_without_headers_uses_the_default_max_age() {
+        let meta = Cache::meta("/vi/a.jpg", &HeaderMap::new()).unwrap();
+
+        assert!(meta.expires >= now() + DEFAULT_MAX_AGE.as_secs() - 1);
+        assert!(meta.last_modified.is_none());
+    }
+
+    #[test]
+    fn evict_removes_the_least_recently_used_entries() {
+        let dir = env::temp_dir().join(format!("ytui-test-cache-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = Cache {
+            dir: Some(dir.clone()),
+            limit: u64::MAX,
+            size: Mutex::new(None),
+        };
+
+        let urls = ["/vi/a.jpg", "/vi/b.jpg", "/vi/c.jpg"];
+        let start = SystemTime::now() - Duration::from_secs(60);
+        for (i, url) in urls.iter().enumerate() {
+            let meta = Meta {
+                url: url.to_string(),
+                ..Meta::default()
+            };
+            cache.store(&meta, &[0; 1000]);
+            let (content_path, _) = cache.paths(url).unwrap();
+            let used = start + Duration::from_secs(i as u64);
+            File::options()
+                .write(true)
+                .open(content_path)
+                .unwrap()
+                .set_modified(used)
+                .unwrap();
+        }
+        // Using the oldest entry makes the second one the least recently used
+        assert!(cache.read(urls[0]).is_some());
+
+        // Room for two entries once down to the eviction ratio
+        let entry_len: u64 = cache.scan().iter().map(|(_, _, len)| *len).max().unwrap();
+        let cache = Cache {
+            limit: (entry_len as f64 * 2.5 / EVICTION_RATIO) as u64,
+            ..cache
+        };
+        let remaining = cache.evict();
+
+        assert!(cache.read(urls[0]).is_some());
+        assert!(cache.read(urls[1]).is_none());
+        assert!(cache.read(urls[2]).is_some());
+        assert_eq!(remaining, cache.scan().iter().map(|(_, _, len)| len).sum::<u64>());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,261 @@
+use super::search::{encode, Query};
+
+use invidious::hidden::{ChannelVideo, Playlist, SearchItem, SearchItemTransition, SearchPlaylistVideo};
+use invidious::PublicItems;
+use serde::{Deserialize, Serialize};
+
+/// Type of the `SearchItem::Unknown` standing for a community post in the results.
+pub const POST_TYPE: &str = "post";
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum Tab {
+    #[default]
+    Videos,
+    Shorts,
+    Live,
+    Playlists,
+    Community,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 5] = [Tab::Videos, Tab::Shorts, Tab::Live, Tab::Playlists, Tab::Community];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Tab::Videos => "Videos",
+            Tab::Shorts => "Shorts",
+            Tab::Live => "Live",
+            Tab::Playlists => "Playlists",
+            Tab::Community => "Community",
+        }
+    }
+
+    /// Endpoint of the tab under `/api/v1/channels/:id`.
+    fn path(&self) -> &'static str {
+        match self {
+            Tab::Videos => "videos",
+            Tab::Shorts => "shorts",
+            Tab::Live => "streams",
+            Tab::Playlists => "playlists",
+            Tab::Community => "community",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|t| t == self).unwrap_or_default()
+    }
+
+    pub fn next(&self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(&self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Whether the order of the items can be chosen.
+    pub fn is_sortable(&self) -> bool {
+        matches!(self, Tab::Videos | Tab::Shorts | Tab::Live | Tab::Playlists)
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum Sort {
+    #[default]
+    Newest,
+    Popular,
+    Oldest,
+}
+
+impl Sort {
+    /// Order shown for `tab`, playlists being sorted by last update rather than popularity.
+    pub fn label(&self, tab: Tab) -> &'static str {
+        match (self, tab) {
+            (Sort::Newest, _) => "newest",
+            (Sort::Popular, Tab::Playlists) => "last updated",
+            (Sort::Popular, _) => "popular",
+            (Sort::Oldest, _) => "oldest",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Sort::Newest => Sort::Popular,
+            Sort::Popular => Sort::Oldest,
+            Sort::Oldest => Sort::Newest,
+        }
+    }
+
+    /// Value of the `sort_by` parameter.
+    fn param(&self, tab: Tab) -> &'static str {
+        match (self, tab) {
+            (Sort::Popular, Tab::Playlists) => "last",
+            (sort, _) => sort.label(tab),
+        }
+    }
+}
+
+/// Post of the community tab of a channel.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Post {
+    pub author: String,
+    #[serde(default)]
+    pub author_id: String,
+    #[serde(default)]
+    pub author_url: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub published: u64,
+    #[serde(default)]
+    pub published_text: String,
+}
+
+/// Response of the tabs of `/api/v1/channels/:id`, missing from the invidious crate.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ChannelPage {
+    #[serde(default)]
+    videos: Vec<ChannelVideo>,
+    #[serde(default)]
+    playlists: Vec<Playlist>,
+    /// Posts of the community tab
+    #[serde(default)]
+    comments: Vec<Post>,
+    pub continuation: Option<String>,
+}
+
+impl PublicItems for ChannelPage {
+    fn url(server: &str, args: String) -> String {
+        format!("{server}/api/v1/channels/{args}")
+    }
+}
+
+impl ChannelPage {
+    /// Path and parameters of a page of a tab, to fetch with `id` set to the path.
+    pub fn request(id: &str, tab: Tab, sort: Sort, continuation: Option<&str>) -> (String, String) {
+        let mut params = match tab.is_sortable() {
+            true => format!("sort_by={}", sort.param(tab)),
+            false => String::new(),
+        };
+        if let Some(continuation) = continuation {
+            if !params.is_empty() {
+                params.push('&');
+            }
+            params.push_str(&format!("continuation={}", encode(continuation)));
+        }
+        (format!("{id}/{}", tab.path()), params)
+    }
+
+    /// Items of the page as they would be in search results.
+    pub fn items(self) -> Vec<SearchItem> {
+        let videos = self.videos.into_iter().map(|video| SearchItem::Video {
+            title: video.title,
+            id: video.id,
+            author: video.author,
+            author_id: video.author_id,
+            author_url: video.author_url,
+            length: video.length.into(),
+            thumbnails: video.thumbnails,
+            description: video.description,
+            description_html: video.description_html,
+            views: video.view_count,
+            published: video.published,
+            published_text: video.published_text,
+            live: video.live,
+            paid: false,
+            premium: video.premium,
+        });
+
+        let playlists = self.playlists.into_iter().map(|playlist| SearchItem::Playlist {
+            title: playlist.title,
+            id: playlist.id,
+            author: playlist.author,
+            author_id: playlist.author_id,
+            author_url: playlist.author_url,
+            author_verified: playlist.verified,
+            video_count: playlist.video_count,
+            videos: playlist
+                .videos
+                .into_iter()
+                .map(|video| SearchPlaylistVideo {
+                    title: video.title,
+                    id: video.id,
+                    length: video.length,
+                    thumbnails: video.thumbnails,
+                })
+                .collect(),
+            thumbnail: playlist.thumbnail,
+        });
+
+        let posts = self.comments.into_iter().map(|post| {
+            SearchItem::Unknown(SearchItemTransition {
+                r#type: String::from(POST_TYPE),
+                title: None,
+                video_id: None,
+                author: post.author,
+                author_id: post.author_id,
+                author_url: post.author_url,
+                video_thumbnails: None,
+                description: Some(post.content),
+                description_html: None,
+                view_count: None,
+                published: Some(post.published),
+                published_text: Some(post.published_text),
+                length_seconds: None,
+                live_now: None,
+                paid: None,
+                premium: None,
+                playlist_id: None,
+                playlist_thumbnail: None,
+                video_count: None,
+                videos: None,
+                verified: None,
+                author_thumbnails: None,
+                sub_count: None,
+            })
+        });
+
+        videos.chain(playlists).chain(posts).collect()
+    }
+}
+
+/// Channel browsed in place of the search results.
+#[derive(Clone)]
+pub struct ChannelView {
+    pub id: String,
+    pub name: String,
+    pub subscribers: u32,
+    pub tab: Tab,
+    pub sort: Sort,
+    /// Text searched within the channel, shown instead of the tab
+    pub search: Option<String>,
+}
+
+impl ChannelView {
+    pub fn new(id: String, name: String, subscribers: u32) -> Self {
+        Self {
+            id,
+            name,
+            subscribers,
+            tab: Tab::default(),
+            sort: Sort::default(),
+            search: None,
+        }
+    }
+
+    /// Query of the items shown.
+    pub fn query(&self) -> Query {
+        match &self.search {
+            Some(input) => Query::ChannelSearch {
+                id: self.id.clone(),
+                input: input.clone(),
+            },
+            None => Query::Channel {
+                id: self.id.clone(),
+                tab: self.tab,
+                sort: self.sort,
+            },
+        }
+    }
+}
@@ -0,0 +1,439 @@
+use super::details::wrap;
+use super::search::encode;
+use super::ui::{format_count, STYLE_AUTHOR, STYLE_ERROR, STYLE_TITLE, STYLE_WARNING};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use invidious::PublicItems;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{ListItem, ListState};
+use serde::{Deserialize, Serialize};
+
+/// Number of rows left below the selection before the next page is fetched.
+const PAGE_PRELOAD: usize = 5;
+
+/// Indentation of the replies under their comment.
+const REPLY_INDENT: &str = "    ";
+
+/// Identifier of the next `Comments`, telling apart the responses of a previous opening.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Response of the `/api/v1/comments` endpoint, whose replies are private in the invidious crate.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CommentsResponse {
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    pub continuation: Option<String>,
+}
+
+impl PublicItems for CommentsResponse {
+    fn url(server: &str, args: String) -> String {
+        format!("{server}/api/v1/comments/{args}")
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    pub author: String,
+    pub content: String,
+    #[serde(default)]
+    pub published_text: String,
+    #[serde(default)]
+    pub like_count: u64,
+    #[serde(default)]
+    pub is_pinned: bool,
+    pub creator_heart: Option<CreatorHeart>,
+    pub replies: Option<Replies>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatorHeart {
+    pub creator_name: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Replies {
+    pub reply_count: u64,
+    pub continuation: String,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum Sort {
+    #[default]
+    Top,
+    New,
+}
+
+impl Sort {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Sort::Top => "top",
+            Sort::New => "new",
+        }
+    }
+
+    pub fn toggle(&self) -> Self {
+        match self {
+            Sort::Top => Sort::New,
+            Sort::New => Sort::Top,
+        }
+    }
+}
+
+/// Page of comments, or of the replies to the comment at index `thread`.
+#[derive(Clone, Debug)]
+pub struct CommentsQuery {
+    /// Identifier of the `Comments` the page is for
+    pub id: usize,
+    pub video_id: String,
+    pub sort: Sort,
+    pub thread: Option<usize>,
+    pub continuation: Option<String>,
+}
+
+impl CommentsQuery {
+    pub fn params(&self) -> String {
+        let mut params = format!("sort_by={}", self.sort.label());
+        if let Some(continuation) = &self.continuation {
+            params.push_str(&format!("&continuation={}", encode(continuation)));
+        }
+        params
+    }
+}
+
+#[derive(Clone)]
+struct Thread {
+    comment: Comment,
+    replies: Vec<Comment>,
+    /// Next page of replies, the first one before they are fetched
+    continuation: Option<String>,
+    expanded: bool,
+    loading: bool,
+}
+
+/// Line of the comments list.
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Row {
+    Comment(usize),
+    Reply(usize, usize),
+    /// More replies of the thread to fetch
+    More(usize),
+}
+
+/// Comments of a video with their reply threads, fetched page by page.
+#[derive(Clone)]
+pub struct Comments {
+    id: usize,
+    pub video_id: String,
+    sort: Sort,
+    threads: Vec<Thread>,
+    continuation: Option<String>,
+    loading: bool,
+    /// Whether the first page was received
+    loaded: bool,
+    selection: ListState,
+}
+
+impl Comments {
+    pub fn new(video_id: String, sort: Sort) -> Self {
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            video_id,
+            sort,
+            threads: vec![],
+            continuation: None,
+            loading: false,
+            loaded: false,
+            selection: ListState::default(),
+        }
+    }
+
+    pub fn sort(&self) -> Sort {
+        self.sort
+    }
+
+    /// Query of the first page of comments.
+    pub fn first_page(&self) -> CommentsQuery {
+        CommentsQuery {
+            id: self.id,
+            video_id: self.video_id.clone(),
+            sort: self.sort,
+            thread: None,
+            continuation: None,
+        }
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.threads.is_empty()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading || self.threads.iter().any(|t| t.loading)
+    }
+
+    /// Whether `query` was made for these comments, rather than before they were sorted or opened again.
+    pub fn is_current(&self, query: &CommentsQuery) -> bool {
+        query.id == self.id
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![];
+        for (t, thread) in self.threads.iter().enumerate() {
+            rows.push(Row::Comment(t));
+            if thread.expanded {
+                rows.extend((0..thread.replies.len()).map(|r| Row::Reply(t, r)));
+                if thread.continuation.is_some() {
+                    rows.push(Row::More(t));
+                }
+            }
+        }
+        rows
+    }
+
+    fn selected_row(&self) -> Option<Row> {
+        self.selection.selected().and_then(|i| self.rows().get(i).copied())
+    }
+
+    fn select_row(&mut self, row: Row) {
+        self.selection.select(self.rows().iter().position(|r| *r == row));
+    }
+
+    /// Query of the next page of comments, if the selection is close enough to the end.
+    pub fn next_page(&self) -> Option<CommentsQuery> {
+        if self.loading || !self.loaded {
+            return None;
+        }
+
+        let selected = self.selection.selected()?;
+        if selected + PAGE_PRELOAD < self.rows().len() {
+            return None;
+        }
+
+        self.continuation.clone().map(|continuation| CommentsQuery {
+            continuation: Some(continuation),
+            ..self.first_page()
+        })
+    }
+
+    /// Expand or collapse the replies of the selected comment, returning the replies to fetch if any.
+    pub fn toggle_selected(&mut self) -> Option<CommentsQuery> {
+        let (t, fetch) = match self.selected_row()? {
+            Row::Comment(t) => {
+                let thread = &mut self.threads[t];
+                if thread.expanded {
+                    thread.expanded = false;
+                    return None;
+                }
+                thread.comment.replies.as_ref()?;
+                thread.expanded = !thread.replies.is_empty();
+                (t, thread.replies.is_empty())
+            }
+            Row::Reply(t, _) => {
+                self.collapse(t);
+                return None;
+            }
+            Row::More(t) => (t, true),
+        };
+
+        let thread = &self.threads[t];
+        match fetch && !thread.loading {
+            true => thread.continuation.clone().map(|continuation| CommentsQuery {
+                thread: Some(t),
+                continuation: Some(continuation),
+                ..self.first_page()
+            }),
+            false => None,
+        }
+    }
+
+    /// Collapse the thread of the selected comment or reply.
+    pub fn collapse_selected(&mut self) {
+        if let Some(Row::Comment(t) | Row::Reply(t, _) | Row::More(t)) = self.selected_row() {
+            self.collapse(t);
+        }
+    }
+
+    fn collapse(&mut self, t: usize) {
+        self.threads[t].expanded = false;
+        self.select_row(Row::Comment(t));
+    }
+
+    pub fn set_loading(&mut self, query: &CommentsQuery) {
+        match query.thread.and_then(|t| self.threads.get_mut(t)) {
+            Some(thread) => thread.loading = true,
+            None => self.loading = true,
+        }
+    }
+
+    /// Forget the failed fetch so that it can be retried.
+    pub fn failed(&mut self, query: &CommentsQuery) {
+        if !self.is_current(query) {
+            return;
+        }
+        match query.thread.and_then(|t| self.threads.get_mut(t)) {
+            Some(thread) => thread.loading = false,
+            None => self.loading = false,
+        }
+    }
+
+    /// Forget the fetches interrupted by leaving the comments.
+    pub fn interrupted(&mut self) {
+        self.loading = false;
+        for thread in &mut self.threads {
+            thread.loading = false;
+        }
+    }
+
+    /// Append a page of comments or replies, ignoring those of another opening or sort order.
+    pub fn append(&mut self, query: &CommentsQuery, response: CommentsResponse) {
+        if !self.is_current(query) {
+            return;
+        }
+
+        match query.thread {
+            Some(t) => {
+                if let Some(thread) = self.threads.get_mut(t) {
+                    thread.loading = false;
+                    thread.expanded = true;
+                    thread.continuation = response.continuation;
+                    thread.replies.extend(response.comments);
+                }
+            }
+            None => {
+                self.loading = false;
+                self.loaded = true;
+                self.continuation = response.continuation;
+                self.threads.extend(response.comments.into_iter().map(|comment| Thread {
+                    continuation: comment.replies.as_ref().map(|r| r.continuation.clone()),
+                    comment,
+                    replies: vec![],
+                    expanded: false,
+                    loading: false,
+                }));
+                if self.selection.selected().is_none() && !self.threads.is_empty() {
+                    self.selection.select(Some(0));
+                }
+            }
+        }
+    }
+
+    pub fn next_selection(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i + 1);
+        self.selection.select(Some(i.min(self.rows().len().saturating_sub(1))));
+    }
+
+    pub fn previous_selection(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i.saturating_sub(1));
+        self.selection.select(Some(i));
+    }
+
+    fn comment_lines<'a>(comment: &'a Comment, indent: &'static str, width: usize) -> Vec<Line<'a>> {
+        let mut header = vec![
+            Span::raw(indent),
+            Span::styled(comment.author.as_str(), STYLE_TITLE),
+            Span::styled(
+                format!(
+                    " · {} likes · {}",
+                    format_count(comment.like_count),
+                    comment.published_text
+                ),
+                STYLE_AUTHOR,
+            ),
+        ];
+        if comment.is_pinned {
+            header.push(Span::styled(" [pinned]", STYLE_WARNING));
+        }
+        if let Some(heart) = &comment.creator_heart {
+            header.push(Span::styled(format!(" ♥ {}", heart.creator_name), STYLE_ERROR));
+        }
+
+        let mut lines = vec![Line::from(header)];
+        for line in wrap(&comment.content, width.saturating_sub(indent.len())) {
+            let text: String = line.into_iter().map(|segment| segment.text).collect();
+            lines.push(Line::from(format!("{indent}{text}")));
+        }
+        lines
+    }
+
+    /// Items of the list, the comments being wrapped to `width` columns.
+    pub fn get_list_split<'a>(&'a mut self, width: u16) -> (Vec<ListItem<'a>>, &'a mut ListState) {
+        let width = width as usize;
+        let mut items: Vec<ListItem> = self
+            .rows()
+            .into_iter()
+            .map(|row| match row {
+                Row::Comment(t) => {
+                    let thread = &self.threads[t];
+                    let mut lines = Self::comment_lines(&thread.comment, "", width);
+                    if let Some(replies) = &thread.comment.replies {
+                        let marker = if thread.expanded { "▾" } else { "▸" };
+                        let mut footer = format!("{marker} {} replies", format_count(replies.reply_count));
+                        if thread.loading && thread.replies.is_empty() {
+                            footer.push_str(" (loading...)");
+                        }
+                        lines.push(Line::from(Span::styled(footer, STYLE_AUTHOR)));
+                    }
+                    ListItem::new(lines)
+                }
+                Row::Reply(t, r) => {
+                    ListItem::new(Self::comment_lines(&self.threads[t].replies[r], REPLY_INDENT, width))
+                }
+                Row::More(t) => ListItem::new(Line::from(Span::styled(
+                    match self.threads[t].loading {
+                        true => format!("{REPLY_INDENT}Loading more replies..."),
+                        false => format!("{REPLY_INDENT}Load more replies"),
+                    },
+                    STYLE_AUTHOR,
+                ))),
+            })
+            .collect();
+
+        if self.loading && self.loaded {
+            items.push(ListItem::new("Loading more...").style(STYLE_AUTHOR));
+        }
+
+        (items, &mut self.selection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response() -> CommentsResponse {
+        CommentsResponse {
+            comments: vec![Comment {
+                author: String::from("author"),
+                content: String::from("content"),
+                published_text: String::new(),
+                like_count: 0,
+                is_pinned: false,
+                creator_heart: None,
+                replies: None,
+            }],
+            continuation: None,
+        }
+    }
+
+    #[test]
+    fn append_ignores_the_pages_of_a_previous_opening() {
+        let first = Comments::new(String::from("id"), Sort::Top);
+        let stale = first.first_page();
+
+        // Sorted twice, back to the same order
+        let mut comments = Comments::new(String::from("id"), Sort::Top);
+        let query = comments.first_page();
+        comments.append(&stale, response());
+        comments.append(&query, response());
+
+        assert_eq!(comments.threads.len(), 1);
+        assert!(!comments.is_current(&stale));
+    }
+}
@@ -0,0 +1,186 @@
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+
+use invidious::hidden::VideoShort;
+use invidious::video::Video;
+use unicode_width::UnicodeWidthChar;
+
+/// Number of videos whose details are kept.
+const DETAILS_SIZE: usize = 100;
+
+pub enum Entry {
+    Loading,
+    Loaded(Box<Video>),
+    Failed,
+}
+
+/// Details of the videos from the video endpoint, fetched once per ID.
+#[derive(Default)]
+pub struct Details {
+    entries: HashMap<String, Entry>,
+    /// IDs from the oldest to the newest requested
+    order: VecDeque<String>,
+    /// Video whose description is scrolled, and by how many lines
+    scroll: (String, u16),
+    /// Last line the description can be scrolled to, known once drawn
+    max_scroll: Cell<u16>,
+    /// Video whose related videos are browsed, and the one selected
+    related: (String, usize),
+}
+
+impl Details {
+    /// Whether the details of `id` must be fetched, marking them as loading if so.
+    pub fn request(&mut self, id: &str) -> bool {
+        if let Some(Entry::Loading | Entry::Loaded(_)) = self.entries.get(id) {
+            return false;
+        }
+
+        self.store(id.to_string(), Entry::Loading);
+        true
+    }
+
+    pub fn insert(&mut self, video: Box<Video>) {
+        self.store(video.id.clone(), Entry::Loaded(video));
+    }
+
+    pub fn failed(&mut self, id: &str) {
+        if let Some(entry @ Entry::Loading) = self.entries.get_mut(id) {
+            *entry = Entry::Failed;
+        }
+    }
+
+    fn store(&mut self, id: String, entry: Entry) {
+        if self.entries.insert(id.clone(), entry).is_none() {
+            self.order.push_back(id);
+        }
+
+        while self.order.len() > DETAILS_SIZE {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Entry> {
+        self.entries.get(id)
+    }
+
+    pub fn video(&self, id: &str) -> Option<&Video> {
+        match self.entries.get(id) {
+            Some(Entry::Loaded(video)) => Some(video),
+            _ => None,
+        }
+    }
+
+    /// First line of the description shown for `id`.
+    pub fn scroll(&self, id: &str) -> u16 {
+        match self.scroll.0 == id {
+            true => self.scroll.1.min(self.max_scroll.get()),
+            false => 0,
+        }
+    }
+
+    pub fn set_max_scroll(&self, max: u16) {
+        self.max_scroll.set(max);
+    }
+
+    pub fn scroll_down(&mut self, id: &str) {
+        let scroll = self.scroll(id);
+        self.scroll = (id.to_string(), (scroll + 1).min(self.max_scroll.get()));
+    }
+
+    pub fn scroll_up(&mut self, id: &str) {
+        let scroll = self.scroll(id);
+        self.scroll = (id.to_string(), scroll.saturating_sub(1));
+    }
+
+    /// Index of the related video selected for `id`.
+    pub fn related_index(&self, id: &str) -> usize {
+        match self.related.0 == id {
+            true => self.related.1,
+            false => 0,
+        }
+    }
+
+    pub fn related(&self, id: &str) -> Option<&VideoShort> {
+        self.video(id)?.recommended_videos.get(self.related_index(id))
+    }
+
+    pub fn next_related(&mut self, id: &str) {
+        let count = self.video(id).map_or(0, |video| video.recommended_videos.len());
+        let i = (self.related_index(id) + 1).min(count.saturating_sub(1));
+        self.related = (id.to_string(), i);
+    }
+
+    pub fn previous_related(&mut self, id: &str) {
+        let i = self.related_index(id).saturating_sub(1);
+        self.related = (id.to_string(), i);
+    }
+}
+
+/// Piece of a wrapped line, `url` being set for the pieces of a link.
+pub struct Segment {
+    pub text: String,
+    pub url: Option<String>,
+}
+
+/// URL starting `word`, without the punctuation following it.
+fn url(word: &str) -> Option<String> {
+    if !(word.starts_with("https://") || word.starts_with("http://")) || word.chars().any(char::is_control) {
+        return None;
+    }
+    Some(
+        word.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '"'])
+            .to_string(),
+    )
+}
+
+/// Wrap `text` on words to `width` columns, splitting the words longer than a line.
+///
+/// The lines are wrapped here rather than by the paragraph to know where the links end up.
+pub fn wrap(text: &str, width: usize) -> Vec<Vec<Segment>> {
+    let width = width.max(1);
+    let mut lines = vec![];
+
+    for paragraph in text.lines() {
+        let mut line: Vec<Segment> = vec![];
+        let mut used = 0;
+
+        for word in paragraph.split_whitespace() {
+            let url = url(word);
+            let word_width: usize = word.chars().map(|c| c.width().unwrap_or(0)).sum();
+
+            if used > 0 && used + 1 + word_width > width {
+                lines.push(std::mem::take(&mut line));
+                used = 0;
+            }
+            if used > 0 {
+                line.push(Segment {
+                    text: String::from(" "),
+                    url: None,
+                });
+                used += 1;
+            }
+
+            let mut piece = String::new();
+            for c in word.chars() {
+                let char_width = c.width().unwrap_or(0);
+                if used + char_width > width && used > 0 {
+                    line.push(Segment {
+                        text: std::mem::take(&mut piece),
+                        url: url.clone(),
+                    });
+                    lines.push(std::mem::take(&mut line));
+                    used = 0;
+                }
+                piece.push(c);
+                used += char_width;
+            }
+            line.push(Segment { text: piece, url });
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
@@ -0,0 +1,345 @@
+use super::ui::format_time;
+use crate::config::DownloadsConfig;
+use crate::Event;
+use crate::EventSender;
+
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use ratatui::widgets::{ListItem, ListState};
+
+/// Prefix of the progress lines printed by yt-dlp with `PROGRESS_TEMPLATE`.
+const PROGRESS_PREFIX: &str = "ytui-progress";
+const PROGRESS_TEMPLATE: &str = "download:ytui-progress %(progress.downloaded_bytes)s %(progress.total_bytes)s \
+     %(progress.total_bytes_estimate)s %(progress.speed)s %(progress.eta)s";
+
+/// Width of the progress bar drawn in the list.
+const BAR_WIDTH: usize = 10;
+
+/// Interval between two checks of whether yt-dlp exited, the child being shared with `cancel_selected`.
+const WAIT_DELAY: Duration = Duration::from_millis(100);
+
+/// Update sent by a download thread as `Event::Download`.
+#[derive(Clone, Debug)]
+pub enum DownloadUpdate {
+    Progress { ratio: f64, speed: f64, eta: u64 },
+    Finished(Result<(), String>),
+}
+
+#[derive(Clone, Debug)]
+pub enum DownloadStatus {
+    Running { ratio: f64, speed: f64, eta: u64 },
+    Done,
+    Cancelled,
+    Failed(String),
+}
+
+pub struct Download {
+    key: usize,
+    id: String,
+    title: String,
+    status: DownloadStatus,
+    child: Option<Arc<Mutex<Child>>>,
+}
+
+pub struct Downloads {
+    event_tx: EventSender,
+    bin: String,
+    dir: PathBuf,
+    items: Vec<Download>,
+    selection: ListState,
+    next_key: usize,
+}
+
+impl Downloads {
+    pub fn new(config: &DownloadsConfig, event_tx: EventSender) -> Self {
+        Self {
+            event_tx,
+            bin: config.command.clone().unwrap_or(String::from("yt-dlp")),
+            dir: config
+                .dir
+                .clone()
+                .or_else(dirs::download_dir)
+                .unwrap_or(PathBuf::from(".")),
+            items: vec![],
+            selection: ListState::default(),
+            next_key: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Start downloading a video in the background.
+    pub fn start(&mut self, id: &str, title: &str) {
+        let mut download = Download {
+            key: 0,
+            id: id.to_string(),
+            title: title.to_string(),
+            status: DownloadStatus::Running {
+                ratio: 0.0,
+                speed: 0.0,
+                eta: 0,
+            },
+            child: None,
+        };
+
+        self.spawn(&mut download);
+        self.items.push(download);
+        if self.selection.selected().is_none() {
+            self.selection.select(Some(0));
+        }
+    }
+
+    /// Run yt-dlp for `download`, under a new key so that updates of a previous run are ignored.
+    fn spawn(&mut self, download: &mut Download) {
+        download.key = self.next_key;
+        self.next_key += 1;
+        download.status = DownloadStatus::Running {
+            ratio: 0.0,
+            speed: 0.0,
+            eta: 0,
+        };
+
+        match self.spawn_child(&download.id) {
+            Ok(mut child) => {
+                let stdout = child.stdout.take();
+                let stderr = child.stderr.take();
+                let child = Arc::new(Mutex::new(child));
+                download.child = Some(child.clone());
+
+                let key = download.key;
+                let event_tx = self.event_tx.clone();
+                thread::spawn(move || {
+                    // Only the last error line is kept to explain a failure
+                    let error = thread::spawn(move || {
+                        stderr.and_then(|stderr| {
+                            BufReader::new(stderr)
+                                .lines()
+                                .map_while(Result::ok)
+                                .filter(|line| line.starts_with("ERROR"))
+                                .last()
+                        })
+                    });
+
+                    if let Some(stdout) = stdout {
+                        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                            if let Some(update) = Self::parse_progress(&line) {
+                                let _ = event_tx.send(Event::Download(key, update));
+                            }
+                        }
+                    }
+
+                    let error = error.join().ok().flatten();
+                    let result = match Self::wait(&child) {
+                        Ok(status) if status.success() => Ok(()),
+                        Ok(status) => Err(error.unwrap_or(format!("yt-dlp exited with {status}"))),
+                        Err(e) => Err(e.to_string()),
+                    };
+                    let _ = event_tx.send(Event::Download(key, DownloadUpdate::Finished(result)));
+                });
+            }
+            Err(e) => download.status = DownloadStatus::Failed(format!("{e:#}")),
+        }
+    }
+
+    /// Wait for yt-dlp to exit without keeping the child locked, so that it can still be killed.
+    fn wait(child: &Mutex<Child>) -> std::io::Result<ExitStatus> {
+        loop {
+            if let Some(status) = child.lock().unwrap().try_wait()? {
+                return Ok(status);
+            }
+            thread::sleep(WAIT_DELAY);
+        }
+    }
+
+    fn spawn_child(&self, id: &str) -> Result<Child> {
+        let output = self.dir.join("%(title)s [%(id)s].%(ext)s");
+        Command::new(&self.bin)
+            .arg("--newline")
+            .arg("--no-colors")
+            .args(["--progress-template", PROGRESS_TEMPLATE])
+            .arg("-o")
+            .arg(output)
+            .arg(format!("https://www.youtube.com/watch?v={id}"))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("cannot start {}", self.bin))
+    }
+
+    fn parse_progress(line: &str) -> Option<DownloadUpdate> {
+        let mut fields = line.strip_prefix(PROGRESS_PREFIX)?.split_whitespace();
+        let mut number = || fields.next().and_then(|f| f.parse::<f64>().ok());
+
+        let downloaded = number().unwrap_or_default();
+        let total = number();
+        let estimate = number();
+        let speed = number().unwrap_or_default();
+        let eta = number().unwrap_or_default() as u64;
+
+        let ratio = match total.or(estimate) {
+            Some(total) if total > 0.0 => (downloaded / total).clamp(0.0, 1.0),
+            _ => 0.0,
+        };
+
+        Some(DownloadUpdate::Progress { ratio, speed, eta })
+    }
+
+    pub fn update(&mut self, key: usize, update: DownloadUpdate) {
+        let Some(download) = self.items.iter_mut().find(|d| d.key == key) else {
+            return;
+        };
+
+        match update {
+            DownloadUpdate::Progress { ratio, speed, eta } => {
+                if let DownloadStatus::Running { .. } = download.status {
+                    download.status = DownloadStatus::Running { ratio, speed, eta };
+                }
+            }
+            DownloadUpdate::Finished(result) => {
+                download.child = None;
+                download.status = match (&download.status, result) {
+                    (DownloadStatus::Cancelled, _) => DownloadStatus::Cancelled,
+                    (_, Ok(())) => DownloadStatus::Done,
+                    (_, Err(e)) => DownloadStatus::Failed(e),
+                };
+            }
+        }
+    }
+
+    pub fn cancel_selected(&mut self) -> Result<()> {
+        let download = self
+            .selection
+            .selected()
+            .and_then(|i| self.items.get_mut(i))
+            .ok_or(anyhow!("no download selected"))?;
+
+        if let Some(child) = &download.child {
+            download.status = DownloadStatus::Cancelled;
+            child.lock().unwrap().kill().context("cannot stop yt-dlp")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn retry_selected(&mut self) {
+        let Some(i) = self.selection.selected() else {
+            return;
+        };
+
+        let mut download = self.items.remove(i);
+        if let DownloadStatus::Cancelled | DownloadStatus::Failed(_) = download.status {
+            self.spawn(&mut download);
+        }
+        self.items.insert(i, download);
+    }
+
+    /// Remove the selected entry from the list if it is not running.
+    pub fn remove_selected(&mut self) {
+        let Some(i) = self.selection.selected() else {
+            return;
+        };
+
+        if self.items[i].child.is_none() {
+            self.items.remove(i);
+            self.selection.select(match self.items.len() {
+                0 => None,
+                len => Some(i.min(len - 1)),
+            });
+        }
+    }
+
+    pub fn next_selection(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i + 1);
+        self.selection.select(Some(i.min(self.items.len().saturating_sub(1))));
+    }
+
+    pub fn previous_selection(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i.saturating_sub(1));
+        self.selection.select(Some(i));
+    }
+
+    pub fn title(&self) -> String {
+        format!("Downloads - {}", self.dir.display())
+    }
+
+    pub fn get_list_split<'a>(&'a mut self) -> (Vec<ListItem<'a>>, &'a mut ListState) {
+        (
+            self.items
+                .iter()
+                .map(|d| {
+                    let status = match &d.status {
+                        DownloadStatus::Running { ratio, speed, eta } => {
+                            let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+                            format!(
+                                "[{}{}] {:>5.1}% {:.1}MiB/s ETA {}",
+                                "#".repeat(filled),
+                                "-".repeat(BAR_WIDTH - filled),
+                                ratio * 100.0,
+                                speed / (1024.0 * 1024.0),
+                                format_time(*eta)
+                            )
+                        }
+                        DownloadStatus::Done => String::from("done"),
+                        DownloadStatus::Cancelled => String::from("cancelled"),
+                        DownloadStatus::Failed(e) => format!("failed: {e}"),
+                    };
+                    ListItem::new(format!("{} {status}", d.title))
+                })
+                .collect(),
+            &mut self.selection,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(line: &str) -> Option<(f64, f64, u64)> {
+        match Downloads::parse_progress(line)? {
+            DownloadUpdate::Progress { ratio, speed, eta } => Some((ratio, speed, eta)),
+            DownloadUpdate::Finished(_) => None,
+        }
+    }
+
+    #[test]
+    fn progress_uses_the_total_size() {
+        assert_eq!(
+            progress("ytui-progress 1048576 4194304 NA 524288.0 6"),
+            Some((0.25, 524288.0, 6))
+        );
+    }
+
+    #[test]
+    fn progress_falls_back_to_the_estimated_size() {
+        assert_eq!(
+            progress("ytui-progress 2097152 NA 8388608.0 1048576.5 6"),
+            Some((0.25, 1048576.5, 6))
+        );
+    }
+
+    #[test]
+    fn progress_with_unknown_fields() {
+        assert_eq!(progress("ytui-progress 1024 NA NA NA NA"), Some((0.0, 0.0, 0)));
+        assert_eq!(progress("ytui-progress NA NA NA NA NA"), Some((0.0, 0.0, 0)));
+    }
+
+    #[test]
+    fn other_lines_are_not_progress() {
+        assert_eq!(progress("[download] Destination: video.webm"), None);
+        assert_eq!(progress("[youtube] dQw4w9WgXcQ: Downloading webpage"), None);
+    }
+}
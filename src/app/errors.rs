@@ -0,0 +1,151 @@
+use super::comments::CommentsQuery;
+use super::search::Query;
+use super::ui::{format_time, STYLE_AUTHOR, STYLE_ERROR, STYLE_WARNING};
+use super::PlaylistAction;
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{ListItem, ListState};
+
+/// Number of reports kept in the log.
+const LOG_SIZE: usize = 100;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    pub fn style(&self) -> Style {
+        match self {
+            Severity::Warning => STYLE_WARNING,
+            Severity::Error => STYLE_ERROR,
+        }
+    }
+}
+
+/// Request which can be sent again after it failed.
+#[derive(Clone, Debug)]
+pub enum Request {
+    /// Search of the given generation
+    Search(usize, Query),
+    /// Next page of the search of the given generation
+    Page(usize),
+    Playlist(String, PlaylistAction),
+    Video(String),
+    Comments(CommentsQuery),
+}
+
+/// Problem reported to the user as `Event::Error`.
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub severity: Severity,
+    pub message: String,
+    pub retry: Option<Request>,
+    /// Only kept in the log, for frequent problems which would hide the others
+    pub quiet: bool,
+}
+
+impl Report {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            retry: None,
+            quiet: false,
+        }
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(message)
+        }
+    }
+
+    pub fn with_retry(mut self, request: Request) -> Self {
+        self.retry = Some(request);
+        self
+    }
+
+    pub fn quiet(mut self) -> Self {
+        self.quiet = true;
+        self
+    }
+}
+
+/// Recent reports, the latest being shown in the status bar until dismissed.
+#[derive(Default)]
+pub struct ErrorLog {
+    reports: VecDeque<(Instant, Report)>,
+    shown: bool,
+    selection: ListState,
+}
+
+impl ErrorLog {
+    pub fn push(&mut self, report: Report) {
+        if self.reports.len() == LOG_SIZE {
+            self.reports.pop_back();
+        }
+        self.shown |= !report.quiet;
+        self.reports.push_front((Instant::now(), report));
+        self.selection.select(Some(0));
+    }
+
+    /// Clear the status bar, the report staying in the log.
+    pub fn dismiss(&mut self) {
+        self.shown = false;
+    }
+
+    /// Report shown in the status bar, the latest one which is not quiet.
+    pub fn latest(&self) -> Option<&Report> {
+        self.reports
+            .iter()
+            .map(|(_, report)| report)
+            .find(|report| !report.quiet)
+            .filter(|_| self.shown)
+    }
+
+    pub fn clear(&mut self) {
+        self.reports.clear();
+        self.shown = false;
+        self.selection.select(None);
+    }
+
+    pub fn next_selection(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i + 1);
+        self.selection.select(Some(i.min(self.reports.len().saturating_sub(1))));
+    }
+
+    pub fn previous_selection(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i.saturating_sub(1));
+        self.selection.select(Some(i));
+    }
+
+    pub fn get_list_split<'a>(&'a mut self) -> (Vec<ListItem<'a>>, &'a mut ListState) {
+        (
+            self.reports
+                .iter()
+                .map(|(time, report)| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("{} ago ", format_time(time.elapsed().as_secs())), STYLE_AUTHOR),
+                        Span::styled(format!("{}: ", report.severity.label()), report.severity.style()),
+                        Span::raw(report.message.as_str()),
+                    ]))
+                })
+                .collect(),
+            &mut self.selection,
+        )
+    }
+}
@@ -0,0 +1,153 @@
+use crate::config::SearchConfig;
+
+use ratatui::widgets::{ListItem, ListState};
+
+/// Search parameters with a single value, the first value meaning no filter.
+pub const PARAMS: &[(&str, &str, &[&str])] = &[
+    ("type", "Type", &["all", "video", "playlist", "channel"]),
+    ("duration", "Duration", &["any", "short", "medium", "long"]),
+    (
+        "date",
+        "Upload date",
+        &["any", "hour", "today", "week", "month", "year"],
+    ),
+    (
+        "sort_by",
+        "Sort by",
+        &["relevance", "rating", "upload_date", "view_count"],
+    ),
+];
+
+/// Values of the `features` search parameter, any number of them can be set.
+pub const FEATURES: &[&str] = &[
+    "hd",
+    "subtitles",
+    "creative_commons",
+    "3d",
+    "live",
+    "4k",
+    "360",
+    "location",
+    "hdr",
+    "vr180",
+];
+
+#[derive(Default, Debug)]
+pub struct Filters {
+    /// Index of the value of each of `PARAMS`
+    params: [usize; PARAMS.len()],
+    features: [bool; FEATURES.len()],
+    selection: ListState,
+}
+
+impl From<&SearchConfig> for Filters {
+    fn from(config: &SearchConfig) -> Self {
+        let mut filters = Self::default();
+        let values = [&config.kind, &config.duration, &config.date, &config.sort_by];
+        for (i, value) in values.into_iter().enumerate() {
+            filters.params[i] = value
+                .as_ref()
+                .and_then(|value| PARAMS[i].2.iter().position(|v| v == value))
+                .unwrap_or_default();
+        }
+        for feature in &config.features {
+            if let Some(i) = FEATURES.iter().position(|f| f == feature) {
+                filters.features[i] = true;
+            }
+        }
+        filters.selection.select(Some(0));
+        filters
+    }
+}
+
+impl Filters {
+    fn len() -> usize {
+        PARAMS.len() + FEATURES.len()
+    }
+
+    /// URL parameters to append to the search query.
+    pub fn params(&self) -> String {
+        let mut params = String::new();
+        for ((name, _, values), value) in PARAMS.iter().zip(self.params) {
+            if value != 0 {
+                params.push_str(&format!("&{name}={}", values[value]));
+            }
+        }
+
+        let features = self.features().collect::<Vec<_>>();
+        if !features.is_empty() {
+            params.push_str(&format!("&features={}", features.join(",")));
+        }
+
+        params
+    }
+
+    fn features(&self) -> impl Iterator<Item = &'static str> + '_ {
+        FEATURES
+            .iter()
+            .zip(self.features)
+            .filter_map(|(feature, enabled)| enabled.then_some(*feature))
+    }
+
+    /// Short description of the active filters, empty if there are none.
+    pub fn summary(&self) -> String {
+        PARAMS
+            .iter()
+            .zip(self.params)
+            .filter(|(_, value)| *value != 0)
+            .map(|((_, label, values), value)| format!("{}: {}", label.to_lowercase(), values[value]))
+            .chain(self.features().map(String::from))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn get_list_split<'a>(&'a mut self) -> (Vec<ListItem<'a>>, &'a mut ListState) {
+        let params = PARAMS
+            .iter()
+            .zip(self.params)
+            .map(|((_, label, values), value)| ListItem::new(format!("{label}: < {} >", values[value])));
+        let features = FEATURES
+            .iter()
+            .zip(self.features)
+            .map(|(feature, enabled)| ListItem::new(format!("[{}] {feature}", if enabled { "x" } else { " " })));
+
+        (params.chain(features).collect(), &mut self.selection)
+    }
+
+    pub fn next_filter(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i + 1);
+        self.selection.select(Some(i.min(Self::len() - 1)));
+    }
+
+    pub fn previous_filter(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i.saturating_sub(1));
+        self.selection.select(Some(i));
+    }
+
+    /// Cycle the value of the selected parameter, or toggle the selected feature.
+    pub fn change_selected(&mut self, forward: bool) {
+        let Some(i) = self.selection.selected() else {
+            return;
+        };
+
+        match PARAMS.get(i) {
+            Some((_, _, values)) => {
+                let len = values.len();
+                self.params[i] = if forward {
+                    (self.params[i] + 1) % len
+                } else {
+                    (self.params[i] + len - 1) % len
+                };
+            }
+            None => {
+                let feature = &mut self.features[i - PARAMS.len()];
+                *feature = !*feature;
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.params = Default::default();
+        self.features = Default::default();
+    }
+}
@@ -0,0 +1,214 @@
+use crate::config::HistoryConfig;
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use ratatui::widgets::{ListItem, ListState};
+
+/// Maximum number of queries kept.
+const HISTORY_SIZE: usize = 1000;
+
+/// Queries searched in the past, stored in `$XDG_DATA_HOME/ytui/history`.
+pub struct History {
+    enabled: bool,
+    /// Queries from the oldest to the newest, without duplicates
+    entries: Vec<String>,
+    /// Entry recalled in the search box, and the input it replaced
+    recall: Option<(usize, String)>,
+    /// Fuzzy picker filter and matching entries, by relevance
+    filter: String,
+    matches: Vec<usize>,
+    selection: ListState,
+}
+
+impl History {
+    fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("ytui").join("history"))
+    }
+
+    /// Load the history file, a missing or unreadable file meaning an empty history.
+    pub fn new(config: &HistoryConfig) -> Self {
+        let entries = match (config.enabled, Self::path()) {
+            (true, Some(path)) => fs::read_to_string(path)
+                .map(|content| content.lines().map(String::from).collect())
+                .unwrap_or_default(),
+            _ => vec![],
+        };
+
+        Self {
+            enabled: config.enabled,
+            entries,
+            recall: None,
+            filter: String::new(),
+            matches: vec![],
+            selection: ListState::default(),
+        }
+    }
+
+    /// Delete the history file.
+    pub fn clear() -> Result<()> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                fs::remove_file(&path).with_context(|| format!("cannot remove {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = Self::path().context("no data directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+        }
+
+        let mut content = self.entries.join("\n");
+        content.push('\n');
+        fs::write(&path, content).with_context(|| format!("cannot write {}", path.display()))
+    }
+
+    /// Record a searched query as the newest entry.
+    pub fn push(&mut self, query: &str) -> Result<()> {
+        self.recall = None;
+        let query = query.trim();
+        if !self.enabled || query.is_empty() {
+            return Ok(());
+        }
+
+        self.entries.retain(|entry| entry != query);
+        self.entries.push(query.to_string());
+        if self.entries.len() > HISTORY_SIZE {
+            self.entries.drain(..self.entries.len() - HISTORY_SIZE);
+        }
+
+        self.save()
+    }
+
+    /// Older entry to show in the search box in place of `input`.
+    pub fn previous(&mut self, input: &str) -> Option<&str> {
+        let i = match &self.recall {
+            Some((i, _)) => i.checked_sub(1)?,
+            None => self.entries.len().checked_sub(1)?,
+        };
+
+        let draft = self.recall.take().map_or(input.to_string(), |(_, draft)| draft);
+        self.recall = Some((i, draft));
+        Some(&self.entries[i])
+    }
+
+    /// Newer entry to show in the search box, the input typed before recalling past the newest.
+    pub fn next(&mut self) -> Option<String> {
+        let (i, draft) = self.recall.take()?;
+        match self.entries.get(i + 1) {
+            Some(entry) => {
+                let entry = entry.clone();
+                self.recall = Some((i + 1, draft));
+                Some(entry)
+            }
+            None => Some(draft),
+        }
+    }
+
+    /// Stop recalling, the input being edited.
+    pub fn reset_recall(&mut self) {
+        self.recall = None;
+    }
+
+    /// Open the fuzzy picker with an empty filter.
+    pub fn start_picker(&mut self) {
+        self.filter.clear();
+        self.update_matches();
+    }
+
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    pub fn push_filter(&mut self, c: char) {
+        self.filter.push(c);
+        self.update_matches();
+    }
+
+    pub fn pop_filter(&mut self) {
+        self.filter.pop();
+        self.update_matches();
+    }
+
+    fn update_matches(&mut self) {
+        let mut matches: Vec<(usize, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| Self::score(&self.filter, entry).map(|score| (score, i)))
+            .collect();
+        // Best score first, then the newest entry
+        matches.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+        self.matches = matches.into_iter().map(|(_, i)| i).collect();
+        self.selection.select((!self.matches.is_empty()).then_some(0));
+    }
+
+    /// Fuzzy match `filter` as a subsequence of `entry`, the lower score the closer.
+    fn score(filter: &str, entry: &str) -> Option<usize> {
+        let entry = entry.to_lowercase();
+        let mut chars = entry.char_indices();
+        let mut first = None;
+        let mut last = 0;
+
+        for c in filter.to_lowercase().chars() {
+            let (i, _) = chars.find(|(_, e)| *e == c)?;
+            first.get_or_insert(i);
+            last = i;
+        }
+
+        // Matches spread over a wide span score worse
+        Some(last - first.unwrap_or_default())
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.selection
+            .selected()
+            .and_then(|i| self.matches.get(i))
+            .map(|i| self.entries[*i].as_str())
+    }
+
+    /// Forget the selected entry of the picker.
+    pub fn remove_selected(&mut self) -> Result<()> {
+        let Some(i) = self.selection.selected().and_then(|i| self.matches.get(i).copied()) else {
+            return Ok(());
+        };
+
+        self.entries.remove(i);
+        let selected = self.selection.selected();
+        self.update_matches();
+        self.selection.select(match self.matches.len() {
+            0 => None,
+            len => selected.map(|i| i.min(len - 1)),
+        });
+
+        match self.enabled {
+            true => self.save(),
+            false => Ok(()),
+        }
+    }
+
+    pub fn next_selection(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i + 1);
+        self.selection.select(Some(i.min(self.matches.len().saturating_sub(1))));
+    }
+
+    pub fn previous_selection(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i.saturating_sub(1));
+        self.selection.select(Some(i));
+    }
+
+    pub fn get_list_split<'a>(&'a mut self) -> (Vec<ListItem<'a>>, &'a mut ListState) {
+        (
+            self.matches
+                .iter()
+                .map(|i| ListItem::new(self.entries[*i].as_str()))
+                .collect(),
+            &mut self.selection,
+        )
+    }
+}
@@ -0,0 +1,247 @@
+use crate::config::NetworkConfig;
+
+use std::error::Error;
+use std::time::{Duration, Instant, SystemTime};
+
+use invidious::functions::url_params;
+use invidious::PublicItems;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use tokio::time::sleep;
+
+/// Delay before the first retry, doubled for each of the next ones.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest wait accepted from a `Retry-After` header.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// HTTP client shared by every request, keeping connections alive between them.
+#[derive(Clone)]
+pub struct Http {
+    client: Client,
+    retries: u32,
+    /// Delay before the first retry
+    retry_delay: Duration,
+}
+
+impl Http {
+    pub fn new(config: &NetworkConfig) -> reqwest::Result<Self> {
+        let user_agent =
+            config
+                .user_agent
+                .clone()
+                .unwrap_or(format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+
+        let client = Client::builder()
+            .user_agent(user_agent)
+            .connect_timeout(Duration::from_secs(config.connect_timeout))
+            .timeout(Duration::from_secs(config.timeout))
+            .build()?;
+
+        Ok(Self {
+            client,
+            retries: config.retries,
+            retry_delay: RETRY_DELAY,
+        })
+    }
+
+    /// Whether the request should be tried again later.
+    fn is_transient(status: StatusCode) -> bool {
+        status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+    }
+
+    /// Wait asked by the server, either as a delay in seconds or as the date to retry at.
+    fn retry_after(response: &Response) -> Option<Duration> {
+        let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+        let wait = match value.parse() {
+            Ok(seconds) => Duration::from_secs(seconds),
+            // A date already passed asks to retry right away
+            Err(_) => httpdate::parse_http_date(value)
+                .ok()?
+                .duration_since(SystemTime::now())
+                .unwrap_or_default(),
+        };
+        Some(wait.min(MAX_RETRY_AFTER))
+    }
+
+    /// GET `url`, retrying with an exponential backoff while the server is overloaded or failing.
+    ///
+    /// The last response is returned whatever its status.
+    pub async fn get(&self, url: &str) -> Result<Response, Box<dyn Error>> {
+        self.get_with(url, HeaderMap::new()).await
+    }
+
+    /// GET `url` with additional request headers.
+    pub async fn get_with(&self, url: &str, headers: HeaderMap) -> Result<Response, Box<dyn Error>> {
+        let mut delay = self.retry_delay;
+        for _ in 0..self.retries {
+            let response = self.client.get(url).headers(headers.clone()).send().await?;
+            if !Self::is_transient(response.status()) {
+                return Ok(response);
+            }
+
+            sleep(Self::retry_after(&response).unwrap_or(delay)).await;
+            delay *= 2;
+        }
+
+        Ok(self.client.get(url).headers(headers).send().await?)
+    }
+
+    /// Fetch an endpoint of the Invidious API on `instance`.
+    pub async fn fetch<T>(&self, instance: &str, id: Option<&str>, params: Option<&str>) -> Result<T, Box<dyn Error>>
+    where
+        T: PublicItems + DeserializeOwned,
+    {
+        let url = T::url(instance, url_params(id, params));
+        let response = self.get(&url).await?;
+
+        // A failing instance is reported as such, errors of the API are in the content
+        let status = response.status();
+        if Self::is_transient(status) {
+            return Err(format!("answered {status}").into());
+        }
+
+        T::from_str(&response.text().await?)
+    }
+
+    /// Response time of `url`, `None` if it does not answer successfully within `timeout`.
+    pub async fn ping(&self, url: &str, timeout: Duration) -> Option<Duration> {
+        let start = Instant::now();
+        let response = self.client.get(url).timeout(timeout).send().await.ok()?;
+        response.status().is_success().then(|| start.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+
+    /// Serve the given responses in order, one per connection, returning the requests received.
+    fn serve(responses: &[&str]) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/v1/stats", listener.local_addr().unwrap());
+        let responses: Vec<String> = responses.iter().map(|r| r.to_string()).collect();
+
+        let server = thread::spawn(move || {
+            let mut requests = vec![];
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    request.push_str(&line);
+                }
+                requests.push(request);
+                write!(stream, "{response}").unwrap();
+            }
+            requests
+        });
+
+        (url, server)
+    }
+
+    fn response(status: &str, headers: &str) -> String {
+        format!("HTTP/1.1 {status}\r\n{headers}Content-Length: 2\r\nConnection: close\r\n\r\n{{}}")
+    }
+
+    fn http(retries: u32, retry_delay: Duration) -> Http {
+        let config = NetworkConfig {
+            retries,
+            user_agent: Some(String::from("ytui-test")),
+            ..NetworkConfig::default()
+        };
+        Http {
+            retry_delay,
+            ..Http::new(&config).unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn get_with_retries_transient_errors_with_backoff() {
+        let unavailable = response("503 Service Unavailable", "");
+        let ok = response("200 OK", "");
+        let (url, server) = serve(&[&unavailable, &unavailable, &ok]);
+
+        let start = Instant::now();
+        let response = http(3, Duration::from_millis(100)).get(&url).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        // 100ms, then twice as long
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].to_lowercase().contains("user-agent: ytui-test"));
+    }
+
+    #[tokio::test]
+    async fn get_with_returns_the_last_response_once_out_of_retries() {
+        let unavailable = response("503 Service Unavailable", "");
+        let (url, server) = serve(&[&unavailable, &unavailable, &unavailable]);
+
+        let response = http(2, Duration::from_millis(10)).get(&url).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(server.join().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn get_with_does_not_retry_client_errors() {
+        let (url, server) = serve(&[&response("404 Not Found", "")]);
+
+        let response = http(3, Duration::from_millis(10)).get(&url).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_with_waits_as_asked_by_retry_after() {
+        let limited = response("429 Too Many Requests", "Retry-After: 1\r\n");
+        let (url, server) = serve(&[&limited, &response("200 OK", "")]);
+
+        let start = Instant::now();
+        let response = http(3, Duration::from_millis(10)).get(&url).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(start.elapsed() >= Duration::from_secs(1));
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_with_waits_until_the_retry_after_date() {
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(3));
+        let limited = response("503 Service Unavailable", &format!("Retry-After: {date}\r\n"));
+        let (url, server) = serve(&[&limited, &response("200 OK", "")]);
+
+        let start = Instant::now();
+        let response = http(3, Duration::from_millis(10)).get(&url).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        // The date is truncated to the second
+        assert!(start.elapsed() >= Duration::from_secs(1));
+        assert_eq!(server.join().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn get_with_sends_the_extra_headers() {
+        let (url, server) = serve(&[&response("200 OK", "")]);
+        let mut headers = HeaderMap::new();
+        headers.insert("x-test", "value".parse().unwrap());
+
+        http(0, Duration::from_millis(10))
+            .get_with(&url, headers)
+            .await
+            .unwrap();
+
+        assert!(server.join().unwrap()[0].to_lowercase().contains("x-test: value"));
+    }
+}
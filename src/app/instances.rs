@@ -0,0 +1,167 @@
+use super::http::Http;
+use crate::config::NetworkConfig;
+
+use std::error::Error;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use invidious::{InvidiousError, PublicItems};
+use serde::de::DeserializeOwned;
+use tokio::task::JoinSet;
+
+/// Time given to an instance to answer the health check.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug)]
+struct Instance {
+    url: String,
+    /// Response time of the health check, `None` if unchecked or down
+    latency: Option<Duration>,
+}
+
+/// Invidious instances by order of preference, shared by the fetching tasks.
+pub struct Instances {
+    http: Http,
+    list: Mutex<Vec<Instance>>,
+    /// Instance which answered the last request
+    active: Mutex<Option<String>>,
+}
+
+impl Instances {
+    pub fn new(config: &NetworkConfig) -> reqwest::Result<Self> {
+        let mut urls: Vec<String> = config.instances.iter().map(|url| Self::normalize(url)).collect();
+        if urls.is_empty() {
+            urls.push(String::from(invidious::INSTANCE));
+        }
+
+        Ok(Self {
+            http: Http::new(config)?,
+            list: Mutex::new(urls.into_iter().map(|url| Instance { url, latency: None }).collect()),
+            active: Mutex::new(None),
+        })
+    }
+
+    /// Add the missing scheme and remove the trailing slash.
+    fn normalize(url: &str) -> String {
+        let url = url.trim().trim_end_matches('/');
+        match url.contains("://") {
+            true => url.to_string(),
+            false => format!("https://{url}"),
+        }
+    }
+
+    /// Instance of the last successful request, or the preferred one.
+    pub fn active(&self) -> String {
+        if let Some(active) = self.active.lock().unwrap().clone() {
+            return active;
+        }
+        self.list.lock().unwrap()[0].url.clone()
+    }
+
+    /// Short description of the active instance for the status bar.
+    pub fn description(&self) -> String {
+        let active = self.active();
+        let latency = self
+            .list
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|i| i.url == active)
+            .and_then(|i| i.latency);
+        let host = active.split("://").last().unwrap_or(&active).to_string();
+
+        match latency {
+            Some(latency) => format!("{host} {}ms", latency.as_millis()),
+            None => host,
+        }
+    }
+
+    /// Make `url` absolute if it is relative to the active instance.
+    pub fn absolute(&self, url: &str) -> String {
+        match url.starts_with('/') {
+            true => format!("{}{url}", self.active()),
+            false => url.to_string(),
+        }
+    }
+
+    /// Measure the response time of every instance, ranking the fastest first and those down last.
+    pub async fn check(&self) {
+        let urls: Vec<String> = self.list.lock().unwrap().iter().map(|i| i.url.clone()).collect();
+
+        let mut checks = JoinSet::new();
+        for url in urls {
+            let http = self.http.clone();
+            checks.spawn(async move {
+                let latency = http.ping(&format!("{url}/api/v1/stats"), CHECK_TIMEOUT).await;
+                (url, latency)
+            });
+        }
+
+        while let Some(result) = checks.join_next().await {
+            let Ok((url, latency)) = result else {
+                continue;
+            };
+            if let Some(instance) = self.list.lock().unwrap().iter_mut().find(|i| i.url == url) {
+                instance.latency = latency;
+            }
+        }
+
+        // Stable sort keeping the configured order between equals
+        self.list
+            .lock()
+            .unwrap()
+            .sort_by_key(|i| (i.latency.is_none(), i.latency));
+    }
+
+    /// Move a failing instance to the end of the list.
+    fn demote(&self, url: &str) {
+        let mut list = self.list.lock().unwrap();
+        if let Some(i) = list.iter().position(|i| i.url == url) {
+            let mut instance = list.remove(i);
+            instance.latency = None;
+            list.push(instance);
+        }
+    }
+
+    pub fn http(&self) -> &Http {
+        &self.http
+    }
+
+    /// Fetch an endpoint of the API on each instance in turn until one answers.
+    ///
+    /// An error returned by the API itself, such as a rate limit, also moves on to the next instance.
+    /// The instance is only demoted if another one answers, the error being the same everywhere otherwise.
+    pub async fn fetch<T>(&self, id: Option<&str>, params: Option<&str>) -> Result<T, Box<dyn Error>>
+    where
+        T: PublicItems + DeserializeOwned,
+    {
+        let urls: Vec<String> = self.list.lock().unwrap().iter().map(|i| i.url.clone()).collect();
+        let mut failure = String::from("no instance");
+        let mut refused: Vec<String> = vec![];
+
+        for url in urls {
+            match self.http.fetch::<T>(&url, id, params).await {
+                Ok(value) => {
+                    for url in &refused {
+                        self.demote(url);
+                    }
+                    *self.active.lock().unwrap() = Some(url);
+                    return Ok(value);
+                }
+                Err(e) if e.is::<InvidiousError>() => {
+                    failure = e.to_string();
+                    refused.push(url);
+                }
+                Err(e) => {
+                    // An error of the API explains more than an unreachable instance
+                    if refused.is_empty() {
+                        failure = format!("{url}: {e}");
+                    }
+                    self.demote(&url);
+                }
+            }
+        }
+
+        Err(failure.into())
+    }
+}
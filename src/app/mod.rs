@@ -1,46 +1,108 @@
+mod cache;
+mod channel;
+pub mod comments;
+mod details;
+pub mod downloads;
+pub mod errors;
+pub mod filters;
+pub mod history;
+mod http;
+mod instances;
+pub mod mpv;
+mod navigation;
 mod player;
+mod quality;
+mod queue;
 pub mod search;
+mod suggestions;
+mod thumbnails;
 mod ui;
 mod widgets;
 
+use crate::config::Config;
 use crate::Event;
 use crate::EventSender;
+use channel::{ChannelPage, ChannelView, Tab, POST_TYPE};
+use comments::{Comments, CommentsQuery, CommentsResponse};
+use details::{wrap, Details, Entry};
+use downloads::{DownloadUpdate, Downloads};
+use errors::{ErrorLog, Report, Request};
+use filters::Filters;
+use history::History;
+use instances::Instances;
+use mpv::Playback;
+use navigation::{Navigation, View};
 use player::Player;
-use search::Search;
+use quality::Quality;
+use queue::{Queue, QueueItem};
+use search::{encode, playlist_video, related_video, Page, PlaylistInfo, Query, Search, Status};
+use suggestions::{Suggestions, SuggestionsResponse};
+use thumbnails::Thumbnails;
 use ui::*;
 use widgets::Image;
 
 use std::convert::AsRef;
 use std::error::Error;
-use std::io::Cursor;
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
-use image::io::Reader as ImageReader;
+use crossterm::cursor::{MoveTo, RestorePosition, SavePosition};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::queue;
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
 use image::DynamicImage;
-use invidious::hidden::SearchItem::{self, *};
-use invidious::ClientAsync as Client;
-use invidious::MethodAsync;
+use invidious::hidden::SearchItem::*;
+use invidious::hidden::{SearchItemTransition, VideoShort};
+use invidious::{universal, video};
 use ratatui::{
     backend::Backend,
     layout::Alignment,
     layout::{Constraint, Direction, Layout, Rect},
     style::Style,
-    text::Line,
-    widgets::{Block, Borders, List, Paragraph, Wrap},
+    symbols,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, LineGauge, List, ListItem, ListState, Paragraph, Tabs, Wrap},
     Frame,
 };
 use tokio::runtime::Runtime;
 use tokio::select;
 use tokio::task::JoinHandle;
+use tokio::time::sleep;
 use tokio_util::sync::CancellationToken;
 use unicode_width::UnicodeWidthStr;
 
-#[derive(PartialEq, Default, Debug)]
+/// Delay without typing before suggestions are requested.
+const SUGGESTIONS_DELAY: Duration = Duration::from_millis(250);
+
+/// Maximum number of suggestions shown at once.
+const SUGGESTIONS_HEIGHT: usize = 8;
+
+/// Time the selection must stay on a video before its details are fetched.
+const DETAILS_DELAY: Duration = Duration::from_millis(300);
+
+/// What to do with a playlist once fetched.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PlaylistAction {
+    Play,
+    Enqueue,
+}
+
+#[derive(PartialEq, Clone, Copy, Default, Debug)]
 enum State {
     #[default]
     List,
     Search,
     Item,
+    Queue,
+    Quality,
+    Downloads,
+    Filters,
+    History,
+    Errors,
+    Comments,
+    /// Videos related to the one of the details pane
+    Related,
 }
 
 pub struct App {
@@ -50,45 +112,285 @@ pub struct App {
     event_tx: EventSender,
     input: String,
     search: Search,
-    searcher: Option<(CancellationToken, JoinHandle<()>)>,
+    searcher: Option<JoinHandle<()>>,
+    /// Cancels the tasks of the current search
+    token: CancellationToken,
+    pagers: Vec<JoinHandle<()>>,
     player: Player,
+    errors: ErrorLog,
+    playback: Option<Playback>,
+    queue: Queue,
+    quality: Option<Quality>,
+    /// Video the quality popup was opened for, its streams coming with its details
+    quality_video: String,
+    downloads: Downloads,
+    filters: Filters,
+    suggestions: Suggestions,
+    suggester: Option<(CancellationToken, JoinHandle<()>)>,
+    history: History,
+    instances: Arc<Instances>,
+    thumbnails: Thumbnails,
+    details: Details,
+    comments: Option<Comments>,
+    /// Channel browsed in place of the results
+    channel: Option<ChannelView>,
+    /// Pages visited before and after the current one
+    navigation: Navigation,
+    /// Links of the last frame, see `write_links`
+    links: Vec<Link>,
+    /// Identifies the last search, see `start_search`
+    generation: usize,
+    /// Frame of the loading animations
+    tick: usize,
+    /// When to fetch the details of the selected video, once the selection settled
+    details_due: Option<Instant>,
 }
 
 impl App {
-    pub fn new(event_tx: EventSender) -> Self {
-        Self {
+    pub fn new(event_tx: EventSender, config: Config) -> anyhow::Result<Self> {
+        let rt = Runtime::new()?;
+        let instances = Arc::new(Instances::new(&config.network)?);
+        let checked = instances.clone();
+        rt.spawn(async move { checked.check().await });
+
+        Ok(Self {
             running: true,
             state: State::default(),
-            rt: Runtime::new().unwrap(),
-            event_tx,
+            rt,
             input: String::default(),
             search: Search::default(),
             searcher: None,
-            player: Player::new(),
-        }
+            token: CancellationToken::new(),
+            pagers: vec![],
+            player: Player::new(&config.player, event_tx.clone()),
+            errors: ErrorLog::default(),
+            playback: None,
+            queue: Queue::default(),
+            quality: None,
+            quality_video: String::new(),
+            downloads: Downloads::new(&config.downloads, event_tx.clone()),
+            filters: Filters::from(&config.search),
+            suggestions: Suggestions::default(),
+            suggester: None,
+            history: History::new(&config.history),
+            thumbnails: Thumbnails::new(&config.thumbnails, event_tx.clone(), instances.clone()),
+            instances,
+            details: Details::default(),
+            comments: None,
+            channel: None,
+            navigation: Navigation::default(),
+            links: vec![],
+            generation: 0,
+            tick: 0,
+            details_due: None,
+            event_tx,
+        })
     }
 
-    fn handle_event_search(&mut self, code: KeyCode) {
-        match code {
+    fn handle_event_search(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            if key.code == KeyCode::Char('r') {
+                self.stop_suggestions();
+                self.history.start_picker();
+                self.state = State::History;
+            }
+            return;
+        }
+
+        match key.code {
             KeyCode::Char(c) => {
                 self.input.push(c);
+                self.history.reset_recall();
+                self.start_suggestions();
             }
             KeyCode::Backspace => {
                 self.input.pop();
+                self.history.reset_recall();
+                self.start_suggestions();
+            }
+            KeyCode::Down => {
+                if !self.suggestions.is_empty() {
+                    self.suggestions.next_suggestion();
+                } else if let Some(entry) = self.history.next() {
+                    self.input = entry;
+                }
+            }
+            KeyCode::Up => {
+                if self.suggestions.selected().is_some() {
+                    self.suggestions.previous_suggestion();
+                } else if let Some(entry) = self.history.previous(&self.input) {
+                    self.input = entry.to_string();
+                    self.stop_suggestions();
+                    self.suggestions.clear();
+                }
+            }
+            KeyCode::Tab => {
+                if let Some(completion) = self.suggestions.completion() {
+                    self.input = completion.to_string();
+                    self.start_suggestions();
+                }
             }
             KeyCode::Esc => {
                 self.state = State::List;
+                self.stop_suggestions();
             }
             KeyCode::Enter => {
+                if let Some(selected) = self.suggestions.selected() {
+                    self.input = selected.to_string();
+                }
                 self.state = State::List;
-                self.stop_search();
-                self.start_search(self.input.clone());
+                self.stop_suggestions();
+                self.search_input();
+            }
+            _ => {}
+        }
+    }
+
+    /// Search the input with the current filters, or within the browsed channel.
+    fn search_input(&mut self) {
+        self.visit();
+
+        if let Some(channel) = &mut self.channel {
+            channel.search = Some(self.input.clone()).filter(|input| !input.is_empty());
+            let query = channel.query();
+            self.stop_search();
+            self.start_search(query);
+            return;
+        }
+
+        // A new search leaves the channel or playlist
+        self.channel = None;
+        if let Err(e) = self.history.push(&self.input) {
+            self.report(Report::error(format!("{e:#}")));
+        }
+
+        let query = Query::Search {
+            input: self.input.clone(),
+            params: format!("q={}{}", encode(&self.input), self.filters.params()),
+        };
+        self.stop_search();
+        self.start_search(query);
+    }
+
+    fn handle_event_history(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.state = State::Search;
+            }
+            KeyCode::Enter => {
+                if let Some(selected) = self.history.selected() {
+                    self.input = selected.to_string();
+                }
+                self.state = State::Search;
+            }
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.history.next_selection();
+            }
+            KeyCode::Char(c) => {
+                self.history.push_filter(c);
+            }
+            KeyCode::Backspace => {
+                self.history.pop_filter();
+            }
+            KeyCode::Up => {
+                self.history.previous_selection();
+            }
+            KeyCode::Down => {
+                self.history.next_selection();
+            }
+            KeyCode::Delete => {
+                if let Err(e) = self.history.remove_selected() {
+                    self.report(Report::error(format!("{e:#}")));
+                }
             }
             _ => {}
         }
     }
 
+    fn handle_event_filters(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('F') => {
+                self.state = State::List;
+            }
+            KeyCode::Enter => {
+                self.state = State::List;
+                if !self.input.is_empty() {
+                    self.search_input();
+                }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.filters.previous_filter();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.filters.next_filter();
+            }
+            KeyCode::Char('h') | KeyCode::Left => {
+                self.filters.change_selected(false);
+            }
+            KeyCode::Char('l') | KeyCode::Right | KeyCode::Char(' ') => {
+                self.filters.change_selected(true);
+            }
+            KeyCode::Char('c') => {
+                self.filters.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// Playback keys shared by every pane, returns whether the key was handled.
+    fn handle_event_playback(&mut self, code: KeyCode) -> bool {
+        if code == KeyCode::Char('v') {
+            self.toggle_audio_only();
+            return true;
+        }
+
+        let Some(mpv) = self.player.mpv() else {
+            return false;
+        };
+
+        let result = match code {
+            KeyCode::Char(' ') => mpv.toggle_pause(),
+            KeyCode::Left => mpv.seek(-10),
+            KeyCode::Right => mpv.seek(10),
+            KeyCode::Char('-') => mpv.add_volume(-5),
+            KeyCode::Char('+') | KeyCode::Char('=') => mpv.add_volume(5),
+            KeyCode::Char('s') => {
+                self.queue.stop();
+                mpv.stop()
+            }
+            _ => return false,
+        };
+
+        if let Err(e) = result {
+            self.report(Report::error(format!("{e:#}")));
+        }
+
+        true
+    }
+
+    fn toggle_audio_only(&mut self) {
+        if !self.player.supports_audio_only() {
+            self.report(Report::warning("the player cannot play the audio only"));
+            return;
+        }
+
+        let audio_only = !self.player.is_audio_only();
+        let result = self
+            .player
+            .set_audio_only(audio_only)
+            .and_then(|_| Config::set("player", "audio_only", audio_only));
+
+        if let Err(e) = result {
+            self.report(Report::error(format!("{e:#}")));
+        }
+    }
+
     fn handle_event_list(&mut self, code: KeyCode) {
+        // The seek and stop keys switch the tab and order of an open channel while nothing plays
+        if self.handle_event_playback(code) || self.handle_event_channel(code) {
+            return;
+        }
+
         match code {
             KeyCode::Char('q') | KeyCode::Esc => {
                 self.running = false;
@@ -97,26 +399,310 @@ impl App {
             KeyCode::Char('/') => {
                 self.state = State::Search;
             }
+            KeyCode::Char('F') => {
+                self.state = State::Filters;
+            }
             KeyCode::Enter => match self.search.selected_item() {
-                Some((Video { id, .. }, _)) => {
-                    self.player.play_video(id);
+                Some((Video { id, title, .. }, _)) => {
+                    self.errors.dismiss();
+                    self.queue.stop();
+                    self.player.play_video(id, title, 0);
+                }
+                Some((Playlist { id, .. }, _)) => {
+                    self.fetch_playlist(id.clone(), PlaylistAction::Play);
+                }
+                Some((
+                    Channel {
+                        id, name, subscribers, ..
+                    },
+                    _,
+                )) => {
+                    self.open_channel(ChannelView::new(id.clone(), name.clone(), *subscribers));
                 }
                 _ => {}
             },
+            KeyCode::Char('a') => match self.search.selected_item() {
+                Some((Playlist { id, .. }, _)) => {
+                    self.fetch_playlist(id.clone(), PlaylistAction::Enqueue);
+                }
+                Some((item, _)) => {
+                    if let Some(item) = QueueItem::from_search_item(item) {
+                        self.queue.push(item);
+                    }
+                }
+                None => {}
+            },
+            KeyCode::Char('o') => {
+                if let Some((Playlist { id, .. }, _)) = self.search.selected_item() {
+                    self.open_playlist(id.clone());
+                }
+            }
             KeyCode::Char('k') | KeyCode::Up => {
                 self.search.previous_video();
+                self.thumbnails.select(self.search.selected().unwrap_or_default());
+                self.details_due = Some(Instant::now() + DETAILS_DELAY);
             }
             KeyCode::Char('j') | KeyCode::Down => {
                 self.search.next_video();
+                self.thumbnails.select(self.search.selected().unwrap_or_default());
+                self.details_due = Some(Instant::now() + DETAILS_DELAY);
+                self.fetch_next_page();
+            }
+            KeyCode::Char('d') => {
+                self.download_selected();
+            }
+            KeyCode::Char('c') => {
+                self.open_comments();
+            }
+            KeyCode::Backspace => {
+                self.go_back();
             }
             KeyCode::Tab => {
-                self.state = State::Item;
+                self.state = self.next_pane();
             }
             _ => {}
         }
     }
 
+    /// Keys of the open channel, returns whether the key was handled.
+    fn handle_event_channel(&mut self, code: KeyCode) -> bool {
+        // A playlist opened from the channel is shown in place of its tabs
+        let Some(channel) = self.channel.as_mut().filter(|_| self.search.playlist().is_none()) else {
+            return false;
+        };
+
+        match code {
+            KeyCode::Left => channel.tab = channel.tab.previous(),
+            KeyCode::Right => channel.tab = channel.tab.next(),
+            KeyCode::Char(c @ '1'..='5') => channel.tab = Tab::ALL[c as usize - '1' as usize],
+            KeyCode::Char('s') if channel.search.is_none() && channel.tab.is_sortable() => {
+                channel.sort = channel.sort.next()
+            }
+            _ => return false,
+        }
+
+        channel.search = None;
+        let query = channel.query();
+        self.stop_search();
+        self.start_search(query);
+        true
+    }
+
+    /// Browse a channel in place of the results.
+    fn open_channel(&mut self, channel: ChannelView) {
+        self.visit();
+        let query = channel.query();
+        self.channel = Some(channel);
+        self.stop_search();
+        self.start_search(query);
+    }
+
+    /// List the videos of a playlist in place of the results, under the channel it was opened from if any.
+    fn open_playlist(&mut self, id: String) {
+        self.visit();
+        self.stop_search();
+        self.start_search(Query::Playlist(id));
+    }
+
+    /// Related video selected in the details of the selected video.
+    fn selected_related(&self) -> Option<&VideoShort> {
+        match self.search.selected_item() {
+            Some((Video { id, .. }, _)) => self.details.related(id),
+            _ => None,
+        }
+    }
+
+    /// List the videos related to a video in place of the results, from its details when known.
+    fn open_related(&mut self, id: String) {
+        self.visit();
+        self.channel = None;
+        self.state = State::List;
+        let items: Option<Vec<_>> = self
+            .details
+            .video(&id)
+            .map(|video| video.recommended_videos.iter().map(related_video).collect());
+        self.stop_search();
+
+        match items {
+            Some(items) => {
+                self.show_search(Search::new(Query::Related(id), items.into()));
+                self.thumbnails.fetch(&self.rt, self.generation, self.search.items(), 0);
+            }
+            None => self.start_search(Query::Related(id)),
+        }
+    }
+
+    /// Current page, as kept in the navigation history.
+    fn view(&self) -> View {
+        View {
+            generation: self.generation,
+            search: self.search.snapshot(),
+            channel: self.channel.clone(),
+            comments: self.comments.clone(),
+            state: self.state,
+        }
+    }
+
+    /// Remember the current page before leaving it for a new one.
+    fn visit(&mut self) {
+        if matches!(self.search.status(), Status::Idle) && self.channel.is_none() {
+            return;
+        }
+        self.navigation.push(self.view());
+    }
+
+    fn go_back(&mut self) {
+        if let Some(view) = self.navigation.back(self.view()) {
+            self.restore(view);
+        }
+    }
+
+    fn go_forward(&mut self) {
+        if let Some(view) = self.navigation.forward(self.view()) {
+            self.restore(view);
+        }
+    }
+
+    /// Show a page of the navigation history again, as it was left.
+    fn restore(&mut self, view: View) {
+        // The results of the current search are the most recent ones of the page
+        if view.generation != self.generation {
+            self.stop_search();
+            match (view.search.status(), view.search.query()) {
+                // Left before the results arrived
+                (Status::Loading | Status::Failed(_), Some(query)) => self.start_search(query.clone()),
+                _ => {
+                    let mut search = view.search;
+                    // A page being fetched when the page was left will never arrive
+                    search.page_failed();
+                    self.show_search(search);
+                    self.thumbnails.fetch(&self.rt, self.generation, self.search.items(), 0);
+                }
+            }
+        }
+
+        self.channel = view.channel;
+        // The comments shown last are kept to be reopened, unless the page had those of another video
+        if let Some(mut comments) = view.comments {
+            if self.comments.as_ref().is_none_or(|c| c.video_id != comments.video_id) {
+                comments.interrupted();
+                self.comments = Some(comments);
+            }
+        }
+        self.state = view.state;
+    }
+
+    fn handle_event_queue(&mut self, code: KeyCode) {
+        if self.handle_event_playback(code) {
+            return;
+        }
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.running = false;
+                self.stop_search();
+            }
+            KeyCode::Char('/') => {
+                self.state = State::Search;
+            }
+            KeyCode::Enter => {
+                if let Some(QueueItem { id, title, .. }) = self.queue.play_selected() {
+                    self.errors.dismiss();
+                    self.player.play_video(id, title, 0);
+                }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.queue.previous_selection();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.queue.next_selection();
+            }
+            KeyCode::Char('K') => {
+                self.queue.move_selected(-1);
+            }
+            KeyCode::Char('J') => {
+                self.queue.move_selected(1);
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                self.queue.remove_selected();
+                if self.queue.is_empty() {
+                    self.state = State::List;
+                }
+            }
+            KeyCode::Char('z') => {
+                self.queue.toggle_shuffle();
+            }
+            KeyCode::Char('r') => {
+                self.queue.cycle_repeat();
+            }
+            KeyCode::Tab => {
+                self.state = self.next_pane();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_event_downloads(&mut self, code: KeyCode) {
+        if self.handle_event_playback(code) {
+            return;
+        }
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.running = false;
+                self.stop_search();
+            }
+            KeyCode::Char('/') => {
+                self.state = State::Search;
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.downloads.previous_selection();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.downloads.next_selection();
+            }
+            KeyCode::Char('c') => {
+                if let Err(e) = self.downloads.cancel_selected() {
+                    self.report(Report::error(format!("{e:#}")));
+                }
+            }
+            KeyCode::Char('r') => {
+                self.downloads.retry_selected();
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                self.downloads.remove_selected();
+                if self.downloads.is_empty() {
+                    self.state = State::List;
+                }
+            }
+            KeyCode::Tab => {
+                self.state = self.next_pane();
+            }
+            _ => {}
+        }
+    }
+
+    /// Pane focused by Tab after the current one, skipping the empty ones.
+    fn next_pane(&self) -> State {
+        match self.state {
+            State::List => State::Item,
+            State::Item if !self.queue.is_empty() => State::Queue,
+            State::Item | State::Queue if !self.downloads.is_empty() => State::Downloads,
+            _ => State::List,
+        }
+    }
+
+    fn download_selected(&mut self) {
+        if let Some((Video { id, title, .. }, _)) = self.search.selected_item() {
+            self.downloads.start(id, title);
+        }
+    }
+
     fn handle_event_item(&mut self, code: KeyCode) {
+        if self.handle_event_playback(code) {
+            return;
+        }
+
         match code {
             KeyCode::Char('q') | KeyCode::Esc => {
                 self.running = false;
@@ -125,137 +711,1059 @@ impl App {
             KeyCode::Char('/') => {
                 self.state = State::Search;
             }
+            KeyCode::Char('f') => {
+                if let Some((Video { id, .. }, _)) = self.search.selected_item() {
+                    let id = id.clone();
+                    self.state = State::Quality;
+                    self.quality_video = id.clone();
+                    self.quality = self
+                        .details
+                        .video(&id)
+                        .map(|video| Quality::new(video, &self.instances.active(), self.player.quality()));
+                    if self.details.request(&id) {
+                        self.fetch_video(id, false);
+                    }
+                }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                if let Some((Video { id, .. }, _)) = self.search.selected_item() {
+                    self.details.scroll_up(&id.clone());
+                }
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                if let Some((Video { id, .. }, _)) = self.search.selected_item() {
+                    self.details.scroll_down(&id.clone());
+                }
+            }
+            KeyCode::Char('r') if self.selected_related().is_some() => {
+                self.state = State::Related;
+            }
+            KeyCode::Char('o') => {
+                if let Some((Playlist { id, .. }, _)) = self.search.selected_item() {
+                    self.open_playlist(id.clone());
+                    self.state = State::List;
+                }
+            }
+            KeyCode::Char('d') => {
+                self.download_selected();
+            }
+            KeyCode::Char('c') => {
+                self.open_comments();
+            }
+            KeyCode::Backspace => {
+                self.go_back();
+            }
+            KeyCode::Tab => {
+                self.state = self.next_pane();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_event_related(&mut self, code: KeyCode) {
+        if self.handle_event_playback(code) {
+            return;
+        }
+
+        let Some((Video { id, .. }, _)) = self.search.selected_item() else {
+            self.state = State::Item;
+            return;
+        };
+        let id = id.clone();
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc | KeyCode::Char('r') | KeyCode::Backspace => {
+                self.state = State::Item;
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.details.previous_related(&id);
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.details.next_related(&id);
+            }
+            KeyCode::Enter => {
+                if let Some(VideoShort { id, title, .. }) = self.selected_related().cloned() {
+                    self.errors.dismiss();
+                    self.queue.stop();
+                    self.player.play_video(&id, &title, 0);
+                }
+            }
+            KeyCode::Char('a') => {
+                if let Some(item) = self
+                    .selected_related()
+                    .and_then(|r| QueueItem::from_search_item(&related_video(r)))
+                {
+                    self.queue.push(item);
+                }
+            }
+            KeyCode::Char('o') => {
+                self.open_related(id);
+            }
             KeyCode::Tab => {
+                self.state = State::Item;
+                self.state = self.next_pane();
+            }
+            _ => {}
+        }
+    }
+
+    /// Show the comments of the selected video, fetched again only for another video.
+    fn open_comments(&mut self) {
+        let Some((Video { id, .. }, _)) = self.search.selected_item() else {
+            return;
+        };
+        let id = id.clone();
+
+        // Reopened comments are the same page, fetched once
+        if self.comments.as_ref().is_some_and(|c| c.video_id == id) {
+            self.state = State::Comments;
+            return;
+        }
+
+        self.visit();
+        self.state = State::Comments;
+
+        let sort = self.comments.as_ref().map(Comments::sort).unwrap_or_default();
+        let comments = Comments::new(id, sort);
+        let query = comments.first_page();
+        self.comments = Some(comments);
+        self.fetch_comments(query);
+    }
+
+    fn handle_event_comments(&mut self, code: KeyCode) {
+        let Some(comments) = &mut self.comments else {
+            self.state = State::List;
+            return;
+        };
+
+        let query = match code {
+            KeyCode::Char('q') | KeyCode::Esc | KeyCode::Char('c') | KeyCode::Backspace => {
+                // Back to the page the comments were opened over, unless they were reopened on it
+                let over = self
+                    .navigation
+                    .peek_back()
+                    .is_some_and(|view| view.generation == self.generation && view.state != State::Comments);
+                if over {
+                    self.go_back();
+                }
+                // Opened over a page dropped from the history
+                if self.state == State::Comments {
+                    self.state = State::List;
+                }
+                None
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                comments.previous_selection();
+                None
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                comments.next_selection();
+                comments.next_page()
+            }
+            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Char('l') | KeyCode::Right => comments.toggle_selected(),
+            KeyCode::Char('h') | KeyCode::Left => {
+                comments.collapse_selected();
+                None
+            }
+            KeyCode::Char('s') => {
+                *comments = Comments::new(comments.video_id.clone(), comments.sort().toggle());
+                Some(comments.first_page())
+            }
+            _ => None,
+        };
+
+        if let Some(query) = query {
+            self.fetch_comments(query);
+        }
+    }
+
+    fn handle_event_quality(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc | KeyCode::Tab => {
+                self.state = State::Item;
+            }
+            KeyCode::Enter => {
+                if let Some(quality) = &self.quality {
+                    if let Some(stream) = quality.selected_stream() {
+                        self.errors.dismiss();
+                        self.queue.stop();
+                        self.player.play_stream(&quality.id, &quality.title, 0, stream);
+                        self.state = State::Item;
+                    }
+                }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                if let Some(quality) = &mut self.quality {
+                    quality.previous_stream();
+                }
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                if let Some(quality) = &mut self.quality {
+                    quality.next_stream();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) {
+        if key.kind == KeyEventKind::Press {
+            let typing = matches!(self.state, State::Search | State::History);
+            if !typing && (self.handle_event_navigation(key) || self.handle_event_global(key.code)) {
+                return;
+            }
+
+            match self.state {
+                State::List => self.handle_event_list(key.code),
+                State::Search => self.handle_event_search(key),
+                State::Item => self.handle_event_item(key.code),
+                State::Queue => self.handle_event_queue(key.code),
+                State::Quality => self.handle_event_quality(key.code),
+                State::Downloads => self.handle_event_downloads(key.code),
+                State::Filters => self.handle_event_filters(key.code),
+                State::History => self.handle_event_history(key),
+                State::Errors => self.handle_event_errors(key.code),
+                State::Comments => self.handle_event_comments(key.code),
+                State::Related => self.handle_event_related(key.code),
+            }
+        }
+    }
+
+    /// Advance the loading animations, returns whether the ui needs a redraw.
+    pub fn handle_tick_event(&mut self) -> bool {
+        let fetched = self.details_due.is_some_and(|due| due <= Instant::now());
+        if fetched {
+            self.details_due = None;
+            self.fetch_details();
+        }
+
+        let comments = self.state == State::Comments && self.comments.as_ref().is_some_and(Comments::is_loading);
+        if !self.search.is_loading() && !comments {
+            return fetched;
+        }
+
+        self.tick = self.tick.wrapping_add(1);
+        true
+    }
+
+    pub fn handle_fetch_event(&mut self, generation: usize, search: Search) {
+        if generation != self.generation {
+            return;
+        }
+
+        self.search = search;
+        self.thumbnails.fetch(&self.rt, generation, self.search.items(), 0);
+    }
+
+    pub fn handle_page_event(&mut self, generation: usize, page: Page) {
+        if generation != self.generation {
+            return;
+        }
+
+        self.thumbnails
+            .fetch(&self.rt, generation, page.items.iter(), self.search.len());
+        self.search.append(page);
+        self.fetch_next_page();
+    }
+
+    pub fn handle_thumbnail_event(&mut self, generation: usize, i: usize, thumbnail: DynamicImage) {
+        if generation == self.generation {
+            self.search.set_thumbnail(i, thumbnail);
+        }
+    }
+
+    pub fn handle_playlist_event(&mut self, action: PlaylistAction, playlist: invidious::universal::Playlist) {
+        let start = self.queue.len();
+        for video in &playlist.videos {
+            self.queue.push(video.into());
+        }
+
+        if action == PlaylistAction::Play {
+            if let Some(QueueItem { id, title, .. }) = self.queue.play_at(start) {
+                self.errors.dismiss();
+                self.player.play_video(id, title, 0);
+            }
+        }
+    }
+
+    pub fn handle_video_event(&mut self, video: Box<invidious::video::Video>) {
+        if self.state == State::Quality && self.quality.is_none() && self.quality_video == video.id {
+            self.quality = Some(Quality::new(&video, &self.instances.active(), self.player.quality()));
+        }
+        self.details.insert(video);
+    }
+
+    /// Prefetch the details of the selected video unless already known, failures only being logged.
+    fn fetch_details(&mut self) {
+        if let Some((Video { id, .. }, _)) = self.search.selected_item() {
+            let id = id.clone();
+            if self.details.request(&id) {
+                self.fetch_video(id, true);
+            }
+        }
+    }
+
+    pub fn handle_suggestions_event(&mut self, query: String, suggestions: Vec<String>) {
+        // Suggestions for an older input are outdated
+        if self.state == State::Search && query == self.input {
+            self.suggestions.set(suggestions);
+        }
+    }
+
+    pub fn handle_comments_event(&mut self, query: CommentsQuery, response: CommentsResponse) {
+        if let Some(comments) = &mut self.comments {
+            comments.append(&query, response);
+        }
+    }
+
+    pub fn handle_download_event(&mut self, key: usize, update: DownloadUpdate) {
+        self.downloads.update(key, update);
+    }
+
+    pub fn handle_error_event(&mut self, mut report: Report) {
+        match &report.retry {
+            Some(Request::Search(generation, _)) | Some(Request::Page(generation))
+                if *generation != self.generation =>
+            {
+                return;
+            }
+            Some(Request::Search(..)) => self.search.set_failed(report.message.clone()),
+            Some(Request::Page(_)) => self.search.page_failed(),
+            Some(Request::Video(id)) => {
+                // The quality popup waits for these details
+                report.quiet &= !(self.state == State::Quality && self.quality_video == *id);
+                self.details.failed(id);
+            }
+            Some(Request::Comments(query)) => {
+                if let Some(comments) = &mut self.comments {
+                    comments.failed(query);
+                }
+            }
+            _ => {}
+        }
+
+        self.report(report);
+    }
+
+    fn report(&mut self, report: Report) {
+        self.errors.push(report);
+    }
+
+    /// Send the request of the report shown in the status bar again.
+    fn retry(&mut self) {
+        let Some(request) = self.errors.latest().and_then(|report| report.retry.clone()) else {
+            return;
+        };
+
+        self.errors.dismiss();
+        match request {
+            Request::Search(_, query) => {
+                self.stop_search();
+                self.start_search(query);
+            }
+            Request::Page(generation) => {
+                if generation == self.generation {
+                    self.fetch_next_page();
+                }
+            }
+            Request::Playlist(id, action) => self.fetch_playlist(id, action),
+            Request::Video(id) => self.fetch_video(id, false),
+            Request::Comments(query) => {
+                if self.comments.as_ref().is_some_and(|c| c.is_current(&query)) {
+                    self.fetch_comments(query);
+                }
+            }
+        }
+    }
+
+    /// Alt-Left and Alt-Right move through the visited pages, returns whether the key was handled.
+    fn handle_event_navigation(&mut self, key: KeyEvent) -> bool {
+        if !key.modifiers.contains(KeyModifiers::ALT) {
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Left => self.go_back(),
+            KeyCode::Right => self.go_forward(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Keys available everywhere but in text inputs, returns whether the key was handled.
+    fn handle_event_global(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char('E') => {
+                self.state = match self.state {
+                    State::Errors => State::List,
+                    _ => State::Errors,
+                };
+            }
+            KeyCode::Char('R') => self.retry(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn handle_event_errors(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => {
                 self.state = State::List;
             }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.errors.previous_selection();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.errors.next_selection();
+            }
+            KeyCode::Char('c') => {
+                self.errors.clear();
+            }
             _ => {}
         }
-    }
+    }
+
+    pub fn handle_player_exit_event(&mut self, run: usize, success: bool) {
+        if !self.player.exited(run) {
+            return;
+        }
+
+        self.playback = None;
+
+        // A failing player would skip through the whole queue
+        if !success {
+            self.queue.stop();
+        } else if let Some(QueueItem { id, title, .. }) = self.queue.next() {
+            self.player.play_video(id, title, 0);
+        }
+    }
+
+    pub fn handle_playback_event(&mut self, run: usize, playback: Playback) {
+        if self.player.is_current(run) {
+            self.playback = Some(playback);
+        }
+    }
+
+    fn get_border_style(&self, state: State) -> Style {
+        if self.state == state {
+            STYLE_HIGHLIGHT
+        } else {
+            STYLE_DEFAULT
+        }
+    }
+
+    pub fn ui<B: Backend>(&mut self, f: &mut Frame<B>) {
+        let summary = self.filters.summary();
+        let mut search_title = Line::from(match (&self.channel, summary.is_empty()) {
+            (Some(channel), _) => format!("Search in {}", channel.name),
+            (None, true) => String::from("Search"),
+            (None, false) => format!("Search [{summary}]"),
+        });
+        search_title.patch_style(STYLE_TITLE);
+        let mut result_title = Line::from(match self.search.status() {
+            Status::Loading => format!("Results {}", spinner(self.tick)),
+            Status::Loaded if self.search.is_loading() => {
+                format!("Results ({}) {}", self.search.len(), spinner(self.tick))
+            }
+            Status::Loaded => format!("Results ({})", self.search.len()),
+            Status::Idle | Status::Empty | Status::Failed(_) => String::from("Results"),
+        });
+        if let Some(channel) = self
+            .channel
+            .as_ref()
+            .filter(|c| c.search.is_none() && c.tab.is_sortable() && self.search.playlist().is_none())
+        {
+            result_title
+                .spans
+                .push(Span::raw(format!(" [{}]", channel.sort.label(channel.tab))));
+        }
+        result_title.patch_style(STYLE_TITLE);
+
+        let playback_height = if self.playback.is_some() { 3 } else { 0 };
+        let chunks_a = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(3),
+                    Constraint::Min(5),
+                    Constraint::Length(playback_height),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(f.size());
+
+        let search_paragraph = Paragraph::new(self.input.as_str()).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(search_title)
+                .border_style(self.get_border_style(State::Search)),
+        );
+        f.render_widget(search_paragraph, chunks_a[0]);
+        if self.state == State::Search {
+            f.set_cursor(chunks_a[0].x + self.input.width() as u16 + 1, chunks_a[0].y + 1);
+        }
+
+        let chunks_b = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)].as_ref())
+            .split(chunks_a[1]);
+
+        let downloads_height = match self.downloads.len() {
+            0 => 0,
+            len => len.min(6) as u16 + 2,
+        };
+        let chunks_d = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(downloads_height)].as_ref())
+            .split(chunks_b[0]);
+
+        if !self.downloads.is_empty() {
+            self.ui_downloads(f, chunks_d[1]);
+        }
+
+        // A playlist opened from a channel is shown under the tabs of the channel
+        let tabs_height = if self.channel.is_some() { 3 } else { 0 };
+        let playlist_height = if self.search.playlist().is_some() { 6 } else { 0 };
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(tabs_height),
+                    Constraint::Length(playlist_height),
+                    Constraint::Min(5),
+                ]
+                .as_ref(),
+            )
+            .split(chunks_d[0]);
+        if let Some(channel) = &self.channel {
+            self.ui_channel_tabs(f, chunks[0], channel);
+        }
+        if let Some(playlist) = self.search.playlist() {
+            self.ui_playlist_info(f, chunks[1], playlist);
+        }
+        let results_rect = chunks[2];
+
+        let result_block = Block::default()
+            .borders(Borders::ALL)
+            .title(result_title)
+            .border_style(self.get_border_style(State::List));
+        let message = match self.search.status() {
+            Status::Loading => Some((
+                format!("{} Searching {}...", spinner(self.tick), self.search.label()),
+                STYLE_DEFAULT,
+            )),
+            Status::Empty => Some((format!("No results for {}", self.search.label()), STYLE_AUTHOR)),
+            Status::Failed(error) => Some((format!("Search failed: {error}"), STYLE_ERROR)),
+            Status::Idle | Status::Loaded => None,
+        };
+        match message {
+            Some((message, style)) => {
+                let message = Paragraph::new(message)
+                    .style(style)
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap { trim: true })
+                    .block(result_block);
+                f.render_widget(message, results_rect);
+            }
+            None => {
+                let list_split = self.search.get_list_split();
+                let result_list = List::new(list_split.0)
+                    .block(result_block)
+                    .highlight_style(STYLE_HIGHLIGHT_ITEM);
+                f.render_stateful_widget(result_list, results_rect, list_split.1);
+            }
+        }
+
+        let queue_height = match self.queue.len() {
+            0 => 0,
+            len => len.min(8) as u16 + 2,
+        };
+        let chunks_c = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(queue_height)].as_ref())
+            .split(chunks_b[1]);
+
+        let links = match self.search.selected_item() {
+            Some((Video { id, title, author, .. }, thumbnail)) => {
+                self.ui_video(f, chunks_c[0], id, title, author, thumbnail)
+            }
+            Some((
+                Playlist {
+                    title,
+                    author,
+                    video_count,
+                    ..
+                },
+                thumbnail,
+            )) => {
+                self.ui_playlist(f, chunks_c[0], title, author, *video_count, thumbnail);
+                vec![]
+            }
+            Some((Channel { name, description, .. }, thumbnail)) => {
+                self.ui_channel(f, chunks_c[0], name, description, thumbnail);
+                vec![]
+            }
+            Some((Unknown(post), _)) if post.r#type == POST_TYPE => {
+                self.ui_post(f, chunks_c[0], post);
+                vec![]
+            }
+            _ => {
+                self.ui_empty(f, chunks_c[0]);
+                vec![]
+            }
+        };
+
+        // Hyperlinks would be written over the popups
+        let popup = matches!(
+            self.state,
+            State::Quality | State::Filters | State::History | State::Errors | State::Comments
+        ) || (self.state == State::Search && !self.suggestions.is_empty());
+        self.links = match popup {
+            true => vec![],
+            false => links,
+        };
+
+        if !self.queue.is_empty() {
+            self.ui_queue(f, chunks_c[1]);
+        }
+
+        if let Some(playback) = &self.playback {
+            self.ui_playback(f, chunks_a[2], playback);
+        }
+
+        if let Some(report) = self.errors.latest() {
+            let mut status = vec![
+                Span::styled(format!("{}: ", report.severity.label()), report.severity.style()),
+                Span::raw(report.message.as_str()),
+            ];
+            if report.retry.is_some() {
+                status.push(Span::styled(" (R to retry)", STYLE_AUTHOR));
+            }
+            f.render_widget(Paragraph::new(Line::from(status)), chunks_a[3]);
+        }
+
+        let mut indicators = self.instances.description();
+        if self.player.is_audio_only() {
+            indicators.push_str(" [audio only]");
+        }
+        let indicators = Paragraph::new(indicators)
+            .style(STYLE_AUTHOR)
+            .alignment(Alignment::Right);
+        f.render_widget(indicators, chunks_a[3]);
+
+        if self.state == State::Search && !self.suggestions.is_empty() {
+            let height = (self.suggestions.len().min(SUGGESTIONS_HEIGHT) as u16 + 2).min(chunks_a[1].height);
+            self.ui_suggestions(f, Rect { height, ..chunks_a[1] });
+        }
+
+        if self.state == State::History {
+            self.ui_history(f, centered_rect(60, 50, f.size()));
+        }
+
+        if self.state == State::Errors {
+            self.ui_errors(f, centered_rect(70, 50, f.size()));
+        }
+
+        if self.state == State::Quality {
+            self.ui_quality(f, centered_rect(60, 50, f.size()));
+        }
+
+        if self.state == State::Filters {
+            self.ui_filters(f, centered_rect(40, 60, f.size()));
+        }
+
+        if self.state == State::Comments {
+            self.ui_comments(f, centered_rect(70, 80, f.size()));
+        }
+    }
+
+    fn ui_comments<B: Backend>(&mut self, f: &mut Frame<B>, rect: Rect) {
+        let Some(comments) = &mut self.comments else {
+            return;
+        };
+
+        let mut title = format!("Comments [{}]", comments.sort().label());
+        if comments.is_loading() {
+            title.push_str(&format!(" {}", spinner(self.tick)));
+        }
+        let mut comments_title = Line::from(title);
+        comments_title.patch_style(STYLE_TITLE);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(comments_title)
+            .border_style(STYLE_HIGHLIGHT);
+
+        f.render_widget(Clear, rect);
+        if !comments.is_loaded() || comments.is_empty() {
+            let message = match comments.is_loaded() {
+                true => "No comments",
+                false => "Comments loading...",
+            };
+            f.render_widget(Paragraph::new(message).alignment(Alignment::Center).block(block), rect);
+            return;
+        }
 
-    pub fn is_running(&self) -> bool {
-        self.running
+        let list_split = comments.get_list_split(block.inner(rect).width);
+        let comment_list = List::new(list_split.0)
+            .block(block)
+            .highlight_style(STYLE_HIGHLIGHT_ITEM);
+        f.render_stateful_widget(comment_list, rect, list_split.1);
     }
 
-    pub fn handle_key_event(&mut self, key: KeyEvent) {
-        if key.kind == KeyEventKind::Press {
-            match self.state {
-                State::List => self.handle_event_list(key.code),
-                State::Search => self.handle_event_search(key.code),
-                State::Item => self.handle_event_item(key.code),
-            }
-        }
+    fn ui_suggestions<B: Backend>(&mut self, f: &mut Frame<B>, rect: Rect) {
+        let list_split = self.suggestions.get_list_split();
+        let suggestion_list = List::new(list_split.0)
+            .block(Block::default().borders(Borders::ALL).border_style(STYLE_HIGHLIGHT))
+            .highlight_style(STYLE_HIGHLIGHT_ITEM);
+
+        f.render_widget(Clear, rect);
+        f.render_stateful_widget(suggestion_list, rect, list_split.1);
     }
 
-    pub fn handle_fetch_event(&mut self, search: Search) {
-        self.search = search;
+    fn ui_errors<B: Backend>(&mut self, f: &mut Frame<B>, rect: Rect) {
+        let mut errors_title = Line::from("Errors");
+        errors_title.patch_style(STYLE_TITLE);
+
+        let list_split = self.errors.get_list_split();
+        let error_list = List::new(list_split.0)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(errors_title)
+                    .border_style(STYLE_HIGHLIGHT),
+            )
+            .highlight_style(STYLE_HIGHLIGHT_ITEM);
+
+        f.render_widget(Clear, rect);
+        f.render_stateful_widget(error_list, rect, list_split.1);
     }
 
-    pub fn handle_thumbnail_event(&mut self, i: usize, thumbnail: DynamicImage) {
-        self.search.set_thumbnail(i, thumbnail);
+    fn ui_history<B: Backend>(&mut self, f: &mut Frame<B>, rect: Rect) {
+        let mut history_title = Line::from("History");
+        history_title.patch_style(STYLE_TITLE);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(history_title)
+            .border_style(STYLE_HIGHLIGHT);
+        let inner = block.inner(rect);
+        f.render_widget(Clear, rect);
+        f.render_widget(block, rect);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(inner);
+
+        let filter = format!("> {}", self.history.filter());
+        f.set_cursor(chunks[0].x + filter.width() as u16, chunks[0].y);
+        f.render_widget(Paragraph::new(filter), chunks[0]);
+
+        let list_split = self.history.get_list_split();
+        let history_list = List::new(list_split.0).highlight_style(STYLE_HIGHLIGHT_ITEM);
+        f.render_stateful_widget(history_list, chunks[1], list_split.1);
     }
 
-    fn get_border_style(&self, state: State) -> Style {
-        if self.state == state {
-            STYLE_HIGHLIGHT
-        } else {
-            STYLE_DEFAULT
-        }
+    fn ui_filters<B: Backend>(&mut self, f: &mut Frame<B>, rect: Rect) {
+        let mut filters_title = Line::from("Filters");
+        filters_title.patch_style(STYLE_TITLE);
+
+        let list_split = self.filters.get_list_split();
+        let filter_list = List::new(list_split.0)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(filters_title)
+                    .border_style(STYLE_HIGHLIGHT),
+            )
+            .highlight_style(STYLE_HIGHLIGHT_ITEM);
+
+        f.render_widget(Clear, rect);
+        f.render_stateful_widget(filter_list, rect, list_split.1);
     }
 
-    pub fn ui<B: Backend>(&mut self, f: &mut Frame<B>) {
-        let mut search_title = Line::from("Search");
-        search_title.patch_style(STYLE_TITLE);
-        let mut result_title = Line::from("Results");
-        result_title.patch_style(STYLE_TITLE);
+    fn ui_quality<B: Backend>(&mut self, f: &mut Frame<B>, rect: Rect) {
+        let mut quality_title = Line::from("Quality");
+        quality_title.patch_style(STYLE_TITLE);
 
-        let chunks_a = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(5)].as_ref())
-            .split(f.size());
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(quality_title)
+            .border_style(STYLE_HIGHLIGHT);
 
-        let search_paragraph = Paragraph::new(self.input.as_str()).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(search_title)
-                .border_style(self.get_border_style(State::Search)),
-        );
-        f.render_widget(search_paragraph, chunks_a[0]);
-        if self.state == State::Search {
-            f.set_cursor(chunks_a[0].x + self.input.width() as u16 + 1, chunks_a[0].y + 1);
+        f.render_widget(Clear, rect);
+        match &mut self.quality {
+            Some(quality) => {
+                let list_split = quality.get_list_split();
+                let stream_list = List::new(list_split.0)
+                    .block(block)
+                    .highlight_style(STYLE_HIGHLIGHT_ITEM);
+                f.render_stateful_widget(stream_list, rect, list_split.1);
+            }
+            None => f.render_widget(
+                Paragraph::new("Formats loading...")
+                    .alignment(Alignment::Center)
+                    .block(block),
+                rect,
+            ),
         }
+    }
 
-        let chunks_b = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)].as_ref())
-            .split(chunks_a[1]);
+    fn ui_queue<B: Backend>(&mut self, f: &mut Frame<B>, rect: Rect) {
+        let mut queue_title = Line::from(self.queue.title());
+        queue_title.patch_style(STYLE_TITLE);
 
-        let border = self.get_border_style(State::List);
-        let mut list_split = self.search.get_list_split();
-        let result_list = List::new(list_split.0)
+        let border = self.get_border_style(State::Queue);
+        let list_split = self.queue.get_list_split();
+        let queue_list = List::new(list_split.0)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(result_title)
+                    .title(queue_title)
                     .border_style(border),
             )
             .highlight_style(STYLE_HIGHLIGHT_ITEM);
-        f.render_stateful_widget(result_list, chunks_b[0], &mut list_split.1);
+        f.render_stateful_widget(queue_list, rect, list_split.1);
+    }
 
-        match self.search.selected_item() {
-            Some((Video { title, author, .. }, thumbnail)) => {
-                self.ui_video(f, chunks_b[1], title, author, thumbnail);
-            }
-            Some((Playlist { title, author, .. }, thumbnail)) => {
-                self.ui_playlist(f, chunks_b[1], title, author);
-            }
-            Some((Channel { name, description, .. }, thumbnail)) => {
-                self.ui_channel(f, chunks_b[1], name, description, thumbnail);
-            }
-            _ => {
-                self.ui_empty(f, chunks_b[1]);
-            }
-        }
+    fn ui_downloads<B: Backend>(&mut self, f: &mut Frame<B>, rect: Rect) {
+        let mut downloads_title = Line::from(self.downloads.title());
+        downloads_title.patch_style(STYLE_TITLE);
+
+        let border = self.get_border_style(State::Downloads);
+        let list_split = self.downloads.get_list_split();
+        let downloads_list = List::new(list_split.0)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(downloads_title)
+                    .border_style(border),
+            )
+            .highlight_style(STYLE_HIGHLIGHT_ITEM);
+        f.render_stateful_widget(downloads_list, rect, list_split.1);
+    }
+
+    fn ui_playback<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, playback: &Playback) {
+        let mut playback_title = Line::from(if playback.paused { "Paused" } else { "Now playing" });
+        playback_title.patch_style(STYLE_TITLE);
+
+        let ratio = if playback.duration > 0.0 {
+            (playback.position / playback.duration).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let label = format!(
+            "{}  {} / {}  vol {:.0}%",
+            playback.title,
+            format_time(playback.position as u64),
+            format_time(playback.duration as u64),
+            playback.volume
+        );
+
+        let gauge = LineGauge::default()
+            .block(Block::default().borders(Borders::ALL).title(playback_title))
+            .gauge_style(STYLE_HIGHLIGHT)
+            .line_set(symbols::line::THICK)
+            .label(label)
+            .ratio(ratio);
+        f.render_widget(gauge, rect);
     }
 
+    /// Draw the selected video, returning the links of its description.
     fn ui_video<B: Backend>(
         &self,
         f: &mut Frame<B>,
         rect: Rect,
+        id: &str,
         title: &str,
         author: &str,
         thumbnail: &Option<DynamicImage>,
-    ) {
+    ) -> Vec<Link> {
         let mut video_title = Line::from("Video");
         video_title.patch_style(STYLE_TITLE);
 
+        let border_style = match self.state {
+            State::Related => STYLE_HIGHLIGHT,
+            _ => self.get_border_style(State::Item),
+        };
         let block = Block::default()
             .borders(Borders::ALL)
             .title(video_title)
-            .border_style(self.get_border_style(State::Item));
+            .border_style(border_style);
         f.render_widget(block, rect);
 
+        let rest = Self::ui_item_header(f, rect, title, author, thumbnail);
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .margin(1)
-            .constraints([Constraint::Ratio(9, 16), Constraint::Min(1), Constraint::Min(1)].as_ref())
-            .split(rect);
+            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .split(rest);
 
-        match thumbnail {
-            Some(thumbnail) => f.render_widget(Image::new(&thumbnail), chunks[0]),
-            None => f.render_widget(
-                Paragraph::new("Thumbnail loading...").alignment(Alignment::Center),
-                chunks[0],
-            ),
+        let video = match self.details.get(id) {
+            Some(Entry::Loaded(video)) => video,
+            Some(Entry::Failed) => {
+                f.render_widget(Paragraph::new("Details unavailable").style(STYLE_AUTHOR), chunks[0]);
+                return vec![];
+            }
+            Some(Entry::Loading) | None => {
+                f.render_widget(Paragraph::new("Details loading...").style(STYLE_AUTHOR), chunks[0]);
+                return vec![];
+            }
         };
 
-        let title = Paragraph::new(title).style(STYLE_TITLE);
-        f.render_widget(title, chunks[1]);
+        let length = match video.live {
+            true => String::from("live"),
+            false => format_time(video.length.into()),
+        };
+        let mut published = format!("Published {}", format_date(video.published));
+        if !video.published_text.is_empty() {
+            published.push_str(&format!(" ({})", video.published_text));
+        }
+        if !video.genre.is_empty() {
+            published.push_str(&format!(" in {}", video.genre));
+        }
+        let stats = Paragraph::new(vec![
+            Line::from(format!(
+                "{length} · {} views · {} likes",
+                format_count(video.views),
+                format_count(video.likes.into())
+            )),
+            Line::from(published),
+            Line::from(Span::styled(video.keywords.join(", "), STYLE_AUTHOR)),
+        ]);
+        f.render_widget(stats, chunks[0]);
 
-        let author = Paragraph::new(author).style(STYLE_AUTHOR);
-        f.render_widget(author, chunks[2]);
+        let bottom = match video.recommended_videos.is_empty() {
+            true => vec![chunks[1]],
+            false => Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(chunks[1])
+                .to_vec(),
+        };
+        if let Some(rect) = bottom.get(1) {
+            self.ui_related(f, *rect, id, video);
+        }
+
+        let mut description_title = Line::from("Description");
+        description_title.patch_style(STYLE_TITLE);
+        let block = Block::default().borders(Borders::TOP).title(description_title);
+        let inner = block.inner(bottom[0]);
+        f.render_widget(block, bottom[0]);
+
+        let lines = wrap(&video.description, inner.width.into());
+        self.details
+            .set_max_scroll((lines.len() as u16).saturating_sub(inner.height));
+        let scroll = self.details.scroll(id);
+
+        let mut links = vec![];
+        for (row, line) in lines.iter().skip(scroll.into()).take(inner.height.into()).enumerate() {
+            let mut x = inner.x;
+            for segment in line {
+                if let Some(url) = &segment.url {
+                    links.push(Link {
+                        x,
+                        y: inner.y + row as u16,
+                        text: segment.text.clone(),
+                        url: url.clone(),
+                    });
+                }
+                x += segment.text.width() as u16;
+            }
+        }
+
+        let lines: Vec<Line> = lines
+            .into_iter()
+            .map(|line| {
+                Line::from(
+                    line.into_iter()
+                        .map(|segment| match segment.url {
+                            Some(_) => Span::styled(segment.text, STYLE_LINK),
+                            None => Span::raw(segment.text),
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines).scroll((scroll, 0)), inner);
+
+        links
+    }
+
+    /// Draw the videos recommended alongside `video`, browsed with `r`.
+    fn ui_related<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, id: &str, video: &video::Video) {
+        let mut related_title = Line::from(match self.state {
+            State::Related => format!("Related ({}) (o to open all)", video.recommended_videos.len()),
+            _ => format!("Related ({}) (r to browse)", video.recommended_videos.len()),
+        });
+        related_title.patch_style(STYLE_TITLE);
+        let block = Block::default()
+            .borders(Borders::TOP)
+            .title(related_title)
+            .border_style(self.get_border_style(State::Related));
+
+        let items: Vec<ListItem> = video
+            .recommended_videos
+            .iter()
+            .map(|related| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(related.title.as_str()),
+                    Span::styled(
+                        format!("  {} · {}", related.author, format_time(related.length.into())),
+                        STYLE_AUTHOR,
+                    ),
+                ]))
+            })
+            .collect();
+
+        let mut state = ListState::default();
+        if self.state == State::Related {
+            state.select(Some(self.details.related_index(id)));
+        }
+        let list = List::new(items).block(block).highlight_style(STYLE_HIGHLIGHT_ITEM);
+        f.render_stateful_widget(list, rect, &mut state);
     }
 
-    fn ui_playlist<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, title: &str, author: &str) {
+    /// Make the links of the last frame clickable, as ratatui cannot draw OSC 8 hyperlinks.
+    ///
+    /// The text of the links is written again over itself, wrapped in the escape sequences.
+    pub fn write_links(&self, out: &mut impl Write) -> io::Result<()> {
+        if self.links.is_empty() {
+            return Ok(());
+        }
+
+        queue!(out, SavePosition)?;
+        for link in &self.links {
+            queue!(
+                out,
+                MoveTo(link.x, link.y),
+                SetForegroundColor(Color::Blue),
+                SetAttribute(Attribute::Underlined),
+                Print(format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", link.url, link.text)),
+                SetAttribute(Attribute::Reset),
+                ResetColor
+            )?;
+        }
+        queue!(out, RestorePosition)?;
+        out.flush()
+    }
+
+    fn ui_playlist<B: Backend>(
+        &self,
+        f: &mut Frame<B>,
+        rect: Rect,
+        title: &str,
+        author: &str,
+        video_count: u32,
+        thumbnail: &Option<DynamicImage>,
+    ) {
         let mut playlist_title = Line::from("Playlist");
         playlist_title.patch_style(STYLE_TITLE);
 
@@ -265,17 +1773,76 @@ impl App {
             .border_style(self.get_border_style(State::Item));
         f.render_widget(block, rect);
 
+        let rest = Self::ui_item_header(f, rect, title, author, thumbnail);
+
+        let count = Paragraph::new(format!("{} videos (o to open)", format_count(video_count.into())));
+        f.render_widget(count, rest);
+    }
+
+    /// Draw the thumbnail, title and author of an item inside the borders of `rect`, returning the area left below.
+    fn ui_item_header<B: Backend>(
+        f: &mut Frame<B>,
+        rect: Rect,
+        title: &str,
+        author: &str,
+        thumbnail: &Option<DynamicImage>,
+    ) -> Rect {
+        // Terminal cells are about twice as high as wide
+        let thumbnail_height = (rect.width.saturating_sub(2) * 9 / 32).min(rect.height / 2);
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
-            .constraints([Constraint::Min(1), Constraint::Min(1)].as_ref())
+            .constraints(
+                [
+                    Constraint::Length(thumbnail_height),
+                    Constraint::Length(1),
+                    Constraint::Length(1),
+                    Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
             .split(rect);
 
+        match thumbnail {
+            Some(thumbnail) => f.render_widget(Image::new(thumbnail), chunks[0]),
+            None => f.render_widget(
+                Paragraph::new("Thumbnail loading...").alignment(Alignment::Center),
+                chunks[0],
+            ),
+        };
+
         let title = Paragraph::new(title).style(STYLE_TITLE);
-        f.render_widget(title, chunks[0]);
+        f.render_widget(title, chunks[1]);
 
         let author = Paragraph::new(author).style(STYLE_AUTHOR);
-        f.render_widget(author, chunks[1]);
+        f.render_widget(author, chunks[2]);
+
+        chunks[3]
+    }
+
+    fn ui_playlist_info<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, playlist: &PlaylistInfo) {
+        let mut playlist_title = Line::from(playlist.title.as_str());
+        playlist_title.patch_style(STYLE_TITLE);
+
+        let mut lines = vec![Line::from(Span::styled(
+            format!(
+                "{} · {} videos · {} views · updated {}",
+                playlist.author,
+                format_count(playlist.video_count.into()),
+                format_count(playlist.views),
+                format_date(playlist.updated)
+            ),
+            STYLE_AUTHOR,
+        ))];
+        lines.extend(playlist.description.lines().map(Line::from));
+
+        let info = Paragraph::new(lines).wrap(Wrap { trim: true }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(playlist_title)
+                .border_style(self.get_border_style(State::List)),
+        );
+        f.render_widget(info, rect);
     }
 
     fn ui_channel<B: Backend>(
@@ -302,7 +1869,7 @@ impl App {
             .split(rect);
 
         match thumbnail {
-            Some(thumbnail) => f.render_widget(Image::new(&thumbnail), chunks[0]),
+            Some(thumbnail) => f.render_widget(Image::new(thumbnail), chunks[0]),
             None => f.render_widget(
                 Paragraph::new("Thumbnail loading...").alignment(Alignment::Center),
                 chunks[0],
@@ -318,6 +1885,55 @@ impl App {
         f.render_widget(description, chunks[2]);
     }
 
+    fn ui_channel_tabs<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, channel: &ChannelView) {
+        let mut channel_title = Line::from(format!(
+            "{} ({} subscribers)",
+            channel.name,
+            format_count(channel.subscribers.into())
+        ));
+        channel_title.patch_style(STYLE_TITLE);
+
+        let tabs = Tabs::new(Tab::ALL.iter().map(|tab| Line::from(tab.title())).collect())
+            .select(channel.tab.index())
+            .highlight_style(STYLE_HIGHLIGHT_ITEM)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(channel_title)
+                    .border_style(self.get_border_style(State::List)),
+            );
+        f.render_widget(tabs, rect);
+    }
+
+    fn ui_post<B: Backend>(&self, f: &mut Frame<B>, rect: Rect, post: &SearchItemTransition) {
+        let mut post_title = Line::from("Post");
+        post_title.patch_style(STYLE_TITLE);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(post_title)
+            .border_style(self.get_border_style(State::Item));
+        f.render_widget(block, rect);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Length(1), Constraint::Min(1)].as_ref())
+            .split(rect);
+
+        let author = Line::from(vec![
+            Span::styled(post.author.as_str(), STYLE_TITLE),
+            Span::styled(
+                format!(" · {}", post.published_text.as_deref().unwrap_or_default()),
+                STYLE_AUTHOR,
+            ),
+        ]);
+        f.render_widget(Paragraph::new(author), chunks[0]);
+
+        let content = Paragraph::new(post.description.as_deref().unwrap_or_default()).wrap(Wrap { trim: false });
+        f.render_widget(content, chunks[1]);
+    }
+
     fn ui_empty<B: Backend>(&self, f: &mut Frame<B>, rect: Rect) {
         let help = Paragraph::new("Hello World!").alignment(Alignment::Center).block(
             Block::default()
@@ -327,66 +1943,318 @@ impl App {
         f.render_widget(help, rect);
     }
 
-    fn start_search(&mut self, input: String) {
+    /// Replace the results, the events of the previous ones being dropped from now on.
+    fn show_search(&mut self, search: Search) {
+        self.generation += 1;
+        self.search = search;
+    }
+
+    fn start_search(&mut self, query: Query) {
         assert!(self.searcher.is_none());
 
-        let token = CancellationToken::new();
-        let join = self
-            .rt
-            .spawn(Self::run_search(self.event_tx.clone(), token.clone(), input));
+        self.show_search(Search::loading(query.clone()));
 
-        self.searcher = Some((token, join));
+        let join = self.rt.spawn(Self::run_search(
+            self.event_tx.clone(),
+            self.instances.clone(),
+            self.token.clone(),
+            self.generation,
+            query,
+        ));
+
+        self.searcher = Some(join);
     }
 
     fn stop_search(&mut self) {
-        if let Some(mut thread) = self.searcher.take() {
+        self.token.cancel();
+        if let Some(mut searcher) = self.searcher.take() {
+            self.rt.block_on(&mut searcher).unwrap();
+        }
+
+        for mut pager in self.pagers.drain(..) {
+            self.rt.block_on(&mut pager).unwrap();
+        }
+        self.token = CancellationToken::new();
+
+        self.thumbnails.stop(&self.rt);
+
+        self.search = Search::default();
+    }
+
+    /// Request suggestions for the input once typing pauses, cancelling the previous request.
+    fn start_suggestions(&mut self) {
+        self.stop_suggestions();
+        if self.input.is_empty() {
+            self.suggestions.clear();
+            return;
+        }
+
+        let token = CancellationToken::new();
+        let join = self.rt.spawn(Self::run_suggestions(
+            self.event_tx.clone(),
+            self.instances.clone(),
+            token.clone(),
+            self.input.clone(),
+        ));
+
+        self.suggester = Some((token, join));
+    }
+
+    fn stop_suggestions(&mut self) {
+        if let Some(mut thread) = self.suggester.take() {
             thread.0.cancel();
             self.rt.block_on(&mut thread.1).unwrap();
         }
 
-        self.search = Search::default();
+        if self.state != State::Search {
+            self.suggestions.clear();
+        }
     }
 
-    async fn run_search(event_tx: EventSender, token: CancellationToken, input: String) {
+    async fn run_suggestions(
+        event_tx: EventSender,
+        instances: Arc<Instances>,
+        token: CancellationToken,
+        query: String,
+    ) {
         select! {
-            _ = Self::fetch_search(event_tx, input) => {},
-            _ = token.cancelled() => return,
+            _ = async {
+                sleep(SUGGESTIONS_DELAY).await;
+                Self::fetch_suggestions(event_tx, &instances, query).await
+            } => {},
+            _ = token.cancelled() => {},
         };
     }
 
-    async fn fetch_search(event_tx: EventSender, input: String) -> Result<(), Box<dyn Error>> {
-        let client = Client::new(String::from(invidious::INSTANCE), MethodAsync::ReqwestAsync);
-        let input = format!("q={input}");
-        let items = client.search(Some(&input)).await?.items;
+    async fn fetch_suggestions(
+        event_tx: EventSender,
+        instances: &Instances,
+        query: String,
+    ) -> Result<(), Box<dyn Error>> {
+        let params = format!("q={}", encode(&query));
+        let response: SuggestionsResponse = instances.fetch(None, Some(&params)).await?;
 
-        event_tx.send(Event::Fetch(items.clone().into())).unwrap();
+        event_tx.send(Event::Suggestions(query, response.suggestions)).unwrap();
 
-        for i in 0..items.len() {
-            let event_tx = event_tx.clone();
-            let _ = Self::fetch_thumbnail(event_tx, i, items[i].clone()).await;
-        }
+        Ok(())
+    }
+
+    async fn run_search(
+        event_tx: EventSender,
+        instances: Arc<Instances>,
+        token: CancellationToken,
+        generation: usize,
+        query: Query,
+    ) {
+        let retry = Request::Search(generation, query.clone());
+        select! {
+            result = Self::fetch_search(event_tx.clone(), &instances, generation, query) => {
+                if let Err(e) = result {
+                    let report = Report::error(format!("search failed: {e}")).with_retry(retry);
+                    event_tx.send(Event::Error(report)).unwrap();
+                }
+            },
+            _ = token.cancelled() => {},
+        };
+    }
+
+    async fn fetch_search(
+        event_tx: EventSender,
+        instances: &Instances,
+        generation: usize,
+        query: Query,
+    ) -> Result<(), Box<dyn Error>> {
+        let page = Self::fetch_items(instances, &query, 1, None).await?;
+
+        event_tx
+            .send(Event::Fetch(generation, Search::new(query, page)))
+            .unwrap();
 
         Ok(())
     }
 
-    async fn fetch_thumbnail(event_tx: EventSender, i: usize, item: SearchItem) -> Result<(), Box<dyn Error>> {
-        let url = match item {
-            Video { thumbnails, .. } => thumbnails.first().and_then(|t| Some(t.url.clone())),
-            Channel { thumbnails, .. } => thumbnails.first().and_then(|t| Some(t.url.clone())),
-            _ => None,
+    async fn fetch_items(
+        instances: &Instances,
+        query: &Query,
+        page: u32,
+        continuation: Option<String>,
+    ) -> Result<Page, Box<dyn Error>> {
+        let items = match query {
+            Query::Search { params, .. } => {
+                let params = format!("{params}&page={page}");
+                instances.fetch::<universal::Search>(None, Some(&params)).await?.items
+            }
+            Query::Playlist(id) => {
+                let params = format!("page={page}");
+                let playlist = instances.fetch::<universal::Playlist>(Some(id), Some(&params)).await?;
+                // Pages can overlap, the continuation being the index of the last video loaded
+                let last = continuation.as_deref().and_then(|index| index.parse::<u32>().ok());
+                let videos: Vec<_> = playlist
+                    .videos
+                    .iter()
+                    .filter(|video| last.is_none_or(|last| video.index > last))
+                    .collect();
+                return Ok(Page {
+                    continuation: videos.last().map(|video| video.index.to_string()).or(continuation),
+                    items: videos.into_iter().map(playlist_video).collect(),
+                    playlist: Some((&playlist).into()),
+                });
+            }
+            Query::Channel { id, tab, sort } => {
+                let (path, params) = ChannelPage::request(id, *tab, *sort, continuation.as_deref());
+                let page = instances.fetch::<ChannelPage>(Some(&path), Some(&params)).await?;
+                return Ok(Page {
+                    continuation: page.continuation.clone(),
+                    items: page.items(),
+                    playlist: None,
+                });
+            }
+            Query::ChannelSearch { id, input } => {
+                let params = format!("q={}&page={page}", encode(input));
+                instances
+                    .fetch::<invidious::channel::ChannelSearch>(Some(id), Some(&params))
+                    .await?
+                    .items
+            }
+            // The recommendations all come with the video, leaving the next pages empty
+            Query::Related(_) if page > 1 => vec![],
+            Query::Related(id) => instances
+                .fetch::<video::Video>(Some(id), Some("local=true"))
+                .await?
+                .recommended_videos
+                .iter()
+                .map(related_video)
+                .collect(),
         };
 
-        let response = match url {
-            Some(url) => reqwest::get(url).await?,
-            None => return Ok(()),
+        Ok(items.into())
+    }
+
+    /// Fetch the next page of results when the selection gets close to the end of the list.
+    fn fetch_next_page(&mut self) {
+        let Some((query, page, continuation)) = self.search.next_page() else {
+            return;
         };
 
-        let thumbnail = ImageReader::new(Cursor::new(response.bytes().await?.as_ref()))
-            .with_guessed_format()?
-            .decode()?;
+        self.search.set_loading();
+        self.pagers.retain(|pager| !pager.is_finished());
+        self.pagers.push(self.rt.spawn(Self::run_page(
+            self.event_tx.clone(),
+            self.instances.clone(),
+            self.token.clone(),
+            self.generation,
+            query,
+            page,
+            continuation,
+        )));
+    }
 
-        event_tx.send(Event::Thumbnail(i, thumbnail)).unwrap();
+    async fn run_page(
+        event_tx: EventSender,
+        instances: Arc<Instances>,
+        token: CancellationToken,
+        generation: usize,
+        query: Query,
+        page: u32,
+        continuation: Option<String>,
+    ) {
+        select! {
+            _ = Self::fetch_page(event_tx, &instances, generation, query, page, continuation) => {},
+            _ = token.cancelled() => {},
+        };
+    }
 
-        Ok(())
+    async fn fetch_page(
+        event_tx: EventSender,
+        instances: &Instances,
+        generation: usize,
+        query: Query,
+        page: u32,
+        continuation: Option<String>,
+    ) {
+        let event = match Self::fetch_items(instances, &query, page, continuation).await {
+            Ok(page) => Event::Page(generation, page),
+            Err(e) => Event::Error(
+                Report::error(format!("cannot load more results: {e}")).with_retry(Request::Page(generation)),
+            ),
+        };
+
+        event_tx.send(event).unwrap();
+    }
+
+    fn fetch_playlist(&self, id: String, action: PlaylistAction) {
+        let event_tx = self.event_tx.clone();
+        let instances = self.instances.clone();
+        self.rt.spawn(async move {
+            let event = match Self::fetch_whole_playlist(&instances, &id).await {
+                Ok(playlist) => Event::Playlist(action, playlist),
+                Err(e) => Event::Error(
+                    Report::error(format!("cannot load the playlist: {e}")).with_retry(Request::Playlist(id, action)),
+                ),
+            };
+            event_tx.send(event).unwrap();
+        });
+    }
+
+    /// Fetch a playlist with the videos of all its pages.
+    async fn fetch_whole_playlist(instances: &Instances, id: &str) -> Result<universal::Playlist, Box<dyn Error>> {
+        let mut playlist = instances.fetch::<universal::Playlist>(Some(id), Some("page=1")).await?;
+
+        for page in 2.. {
+            let params = format!("page={page}");
+            let next = instances.fetch::<universal::Playlist>(Some(id), Some(&params)).await?;
+            // Pages can overlap, and stop at the first one without new videos
+            let last = playlist.videos.last().map(|video| video.index);
+            let videos: Vec<_> = next
+                .videos
+                .into_iter()
+                .filter(|video| last.is_none_or(|last| video.index > last))
+                .collect();
+            if videos.is_empty() {
+                break;
+            }
+            playlist.videos.extend(videos);
+        }
+
+        Ok(playlist)
+    }
+
+    fn fetch_comments(&mut self, query: CommentsQuery) {
+        if let Some(comments) = &mut self.comments {
+            comments.set_loading(&query);
+        }
+
+        let event_tx = self.event_tx.clone();
+        let instances = self.instances.clone();
+        self.rt.spawn(async move {
+            let event = match instances
+                .fetch::<CommentsResponse>(Some(&query.video_id), Some(&query.params()))
+                .await
+            {
+                Ok(response) => Event::Comments(query, response),
+                Err(e) => Event::Error(
+                    Report::error(format!("cannot load the comments: {e}")).with_retry(Request::Comments(query)),
+                ),
+            };
+            event_tx.send(event).unwrap();
+        });
+    }
+
+    /// Fetch the details of a video, `quiet` keeping a failure out of the status bar.
+    fn fetch_video(&self, id: String, quiet: bool) {
+        let event_tx = self.event_tx.clone();
+        let instances = self.instances.clone();
+        self.rt.spawn(async move {
+            let event = match instances.fetch::<video::Video>(Some(&id), Some("local=true")).await {
+                Ok(video) => Event::Video(Box::new(video)),
+                Err(e) => {
+                    let mut report =
+                        Report::error(format!("cannot load the video: {e}")).with_retry(Request::Video(id));
+                    report.quiet = quiet;
+                    Event::Error(report)
+                }
+            };
+            event_tx.send(event).unwrap();
+        });
     }
 }
@@ -0,0 +1,258 @@
+use super::errors::Report;
+use crate::Event;
+use crate::EventSender;
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+
+/// Properties observed through `observe_property`, the index is used as the observer id.
+const PROPERTIES: &[&str] = &["media-title", "time-pos", "duration", "pause", "volume"];
+
+/// How many times to try to connect while mpv is creating its socket.
+const CONNECT_ATTEMPTS: usize = 50;
+const CONNECT_DELAY: Duration = Duration::from_millis(100);
+
+/// Playback state of a running mpv instance.
+#[derive(Clone, Default, Debug)]
+pub struct Playback {
+    pub title: String,
+    pub position: f64,
+    pub duration: f64,
+    pub paused: bool,
+    pub volume: f64,
+}
+
+/// State of the IPC socket, the commands given while mpv starts being sent once connected.
+enum Connection {
+    Connecting(Vec<Value>),
+    Connected(UnixStream),
+    Closed,
+}
+
+/// Client of the mpv JSON IPC protocol.
+pub struct Mpv {
+    socket: PathBuf,
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl Mpv {
+    pub fn new(socket: PathBuf) -> Self {
+        Self {
+            socket,
+            connection: Arc::new(Mutex::new(Connection::Connecting(vec![]))),
+        }
+    }
+
+    pub fn socket(&self) -> &PathBuf {
+        &self.socket
+    }
+
+    /// Connect to mpv in the background and forward its playback state as `Event::Playback`.
+    pub fn connect(&self, event_tx: EventSender, run: usize) {
+        let socket = self.socket.clone();
+        let connection = self.connection.clone();
+
+        thread::spawn(move || {
+            let reader = match Self::open(&socket, &connection) {
+                Ok(reader) => reader,
+                Err(e) => {
+                    *connection.lock().unwrap() = Connection::Closed;
+                    let _ = event_tx.send(Event::Error(Report::warning(format!("{e:#}"))));
+                    return;
+                }
+            };
+
+            let mut playback = Playback::default();
+            for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                    continue;
+                };
+                if Self::update(&mut playback, &message)
+                    && event_tx.send(Event::Playback(run, playback.clone())).is_err()
+                {
+                    break;
+                }
+            }
+
+            *connection.lock().unwrap() = Connection::Closed;
+        });
+    }
+
+    fn open(socket: &PathBuf, connection: &Mutex<Connection>) -> Result<UnixStream> {
+        let mut attempts = 0;
+        let reader = loop {
+            match UnixStream::connect(socket) {
+                Ok(reader) => break reader,
+                Err(_) if attempts < CONNECT_ATTEMPTS => {
+                    attempts += 1;
+                    thread::sleep(CONNECT_DELAY);
+                }
+                Err(e) => return Err(e).with_context(|| format!("cannot connect to mpv at {}", socket.display())),
+            }
+        };
+
+        let mut writer = reader.try_clone()?;
+        for (id, name) in PROPERTIES.iter().enumerate() {
+            Self::write(&mut writer, json!(["observe_property", id, name]))?;
+        }
+
+        let mut connection = connection.lock().unwrap();
+        if let Connection::Connecting(pending) = &mut *connection {
+            for command in pending.drain(..) {
+                Self::write(&mut writer, command)?;
+            }
+        }
+        *connection = Connection::Connected(writer);
+
+        Ok(reader)
+    }
+
+    /// Apply a `property-change` event, returns whether the displayed state changed.
+    fn update(playback: &mut Playback, message: &Value) -> bool {
+        if message["event"] != "property-change" {
+            return false;
+        }
+
+        let data = &message["data"];
+        match message["name"].as_str() {
+            Some("media-title") => playback.title = data.as_str().unwrap_or_default().to_string(),
+            Some("time-pos") => {
+                // Only redraw once per second, mpv reports the position on every frame
+                let position = data.as_f64().unwrap_or_default();
+                let changed = position.trunc() != playback.position.trunc();
+                playback.position = position;
+                return changed;
+            }
+            Some("duration") => playback.duration = data.as_f64().unwrap_or_default(),
+            Some("pause") => playback.paused = data.as_bool().unwrap_or_default(),
+            Some("volume") => playback.volume = data.as_f64().unwrap_or_default(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn write(stream: &mut UnixStream, command: Value) -> Result<()> {
+        writeln!(stream, "{}", json!({ "command": command }))?;
+        Ok(())
+    }
+
+    fn command(&self, command: Value) -> Result<()> {
+        match &mut *self.connection.lock().unwrap() {
+            Connection::Connecting(pending) => {
+                pending.push(command);
+                Ok(())
+            }
+            Connection::Connected(stream) => Self::write(stream, command),
+            Connection::Closed => Err(anyhow!("mpv is not connected")),
+        }
+    }
+
+    pub fn toggle_pause(&self) -> Result<()> {
+        self.command(json!(["cycle", "pause"]))
+    }
+
+    pub fn seek(&self, seconds: i64) -> Result<()> {
+        self.command(json!(["seek", seconds, "relative"]))
+    }
+
+    pub fn add_volume(&self, volume: i64) -> Result<()> {
+        self.command(json!(["add", "volume", volume]))
+    }
+
+    pub fn set_video(&self, video: bool) -> Result<()> {
+        self.command(json!(["set_property", "vid", if video { "auto" } else { "no" }]))
+    }
+
+    pub fn stop(&self) -> Result<()> {
+        self.command(json!(["quit"]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::os::unix::net::UnixListener;
+    use std::process;
+    use std::sync::mpsc::channel;
+
+    fn change(name: &str, data: Value) -> Value {
+        json!({ "event": "property-change", "name": name, "data": data })
+    }
+
+    #[test]
+    fn update_applies_property_changes() {
+        let mut playback = Playback::default();
+
+        assert!(Mpv::update(&mut playback, &change("media-title", json!("Title"))));
+        assert!(Mpv::update(&mut playback, &change("duration", json!(120.5))));
+        assert!(Mpv::update(&mut playback, &change("pause", json!(true))));
+        assert!(Mpv::update(&mut playback, &change("volume", json!(80.0))));
+        assert_eq!(playback.title, "Title");
+        assert_eq!(playback.duration, 120.5);
+        assert!(playback.paused);
+        assert_eq!(playback.volume, 80.0);
+
+        assert!(!Mpv::update(&mut playback, &change("speed", json!(2.0))));
+        assert!(!Mpv::update(&mut playback, &json!({ "event": "seek" })));
+    }
+
+    #[test]
+    fn update_redraws_once_per_second() {
+        let mut playback = Playback::default();
+
+        assert!(Mpv::update(&mut playback, &change("time-pos", json!(1.2))));
+        assert!(!Mpv::update(&mut playback, &change("time-pos", json!(1.7))));
+        assert_eq!(playback.position, 1.7);
+        assert!(Mpv::update(&mut playback, &change("time-pos", json!(2.1))));
+    }
+
+    #[test]
+    fn connect_sends_pending_commands_and_forwards_playback() {
+        let socket = env::temp_dir().join(format!("ytui-test-{}.sock", process::id()));
+        let _ = std::fs::remove_file(&socket);
+
+        let (event_tx, event_rx) = channel();
+        let mpv = Mpv::new(socket.clone());
+        mpv.connect(event_tx, 7);
+        // Given before mpv created its socket
+        mpv.toggle_pause().unwrap();
+
+        let listener = UnixListener::bind(&socket).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+        let mut lines = BufReader::new(server.try_clone().unwrap()).lines();
+
+        for (id, name) in PROPERTIES.iter().enumerate() {
+            let line: Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+            assert_eq!(line, json!({ "command": ["observe_property", id, name] }));
+        }
+        let line: Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+        assert_eq!(line, json!({ "command": ["cycle", "pause"] }));
+
+        writeln!(server, "{}", change("media-title", json!("Title"))).unwrap();
+        match event_rx.recv_timeout(Duration::from_secs(5)) {
+            Ok(Event::Playback(run, playback)) => {
+                assert_eq!(run, 7);
+                assert_eq!(playback.title, "Title");
+            }
+            _ => panic!("expected the playback state"),
+        }
+
+        mpv.seek(10).unwrap();
+        let line: Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+        assert_eq!(line, json!({ "command": ["seek", 10, "relative"] }));
+
+        drop(server);
+        drop(lines);
+        let _ = std::fs::remove_file(&socket);
+    }
+}
@@ -0,0 +1,55 @@
+use super::channel::ChannelView;
+use super::comments::Comments;
+use super::search::Search;
+use super::State;
+
+/// Number of pages kept to go back to.
+const NAVIGATION_SIZE: usize = 50;
+
+/// Page visited: the results with their selection, and what was open over them.
+pub struct View {
+    /// Search of the results, kept as they are when returning to the same generation
+    pub generation: usize,
+    /// Results without their thumbnails, fetched again when the page is shown
+    pub search: Search,
+    pub channel: Option<ChannelView>,
+    pub comments: Option<Comments>,
+    pub state: State,
+}
+
+/// Pages to go back and forward to, like in a web browser.
+#[derive(Default)]
+pub struct Navigation {
+    back: Vec<View>,
+    forward: Vec<View>,
+}
+
+impl Navigation {
+    /// Leave the `current` page for a new one, which forgets the pages to go forward to.
+    pub fn push(&mut self, current: View) {
+        if self.back.len() == NAVIGATION_SIZE {
+            self.back.remove(0);
+        }
+        self.back.push(current);
+        self.forward.clear();
+    }
+
+    /// Page to go back to.
+    pub fn peek_back(&self) -> Option<&View> {
+        self.back.last()
+    }
+
+    /// Previous page, `current` becoming the next one.
+    pub fn back(&mut self, current: View) -> Option<View> {
+        let view = self.back.pop()?;
+        self.forward.push(current);
+        Some(view)
+    }
+
+    /// Next page, `current` becoming the previous one.
+    pub fn forward(&mut self, current: View) -> Option<View> {
+        let view = self.forward.pop()?;
+        self.back.push(current);
+        Some(view)
+    }
+}
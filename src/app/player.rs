@@ -1,22 +1,286 @@
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use super::errors::Report;
+use super::mpv::Mpv;
+use super::quality::Stream;
+use crate::config::PlayerConfig;
+use crate::Event;
+use crate::EventSender;
+
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Command, Stdio};
+use std::thread;
+
+use anyhow::{anyhow, Context, Result};
+
+struct KnownPlayer {
+    name: &'static str,
+    /// Default arguments
+    args: &'static str,
+    /// Arguments disabling video
+    audio_args: &'static str,
+    /// Option taking a youtube-dl format selector, if supported
+    format_arg: Option<&'static str>,
+}
+
+/// Known players, by order of preference.
+const PLAYERS: &[KnownPlayer] = &[
+    KnownPlayer {
+        name: "mpv",
+        args: "--start={start} --force-media-title={title} {url}",
+        audio_args: "--no-video",
+        format_arg: Some("--ytdl-format"),
+    },
+    KnownPlayer {
+        name: "celluloid",
+        args: "--mpv-start={start} --mpv-force-media-title={title} {url}",
+        audio_args: "--mpv-vid=no",
+        format_arg: Some("--mpv-ytdl-format"),
+    },
+    KnownPlayer {
+        name: "vlc",
+        args: "--start-time={start} --meta-title={title} {url}",
+        audio_args: "--no-video",
+        format_arg: None,
+    },
+    KnownPlayer {
+        name: "haruna",
+        args: "{url}",
+        audio_args: "",
+        format_arg: None,
+    },
+    KnownPlayer {
+        name: "smplayer",
+        args: "{url}",
+        audio_args: "",
+        format_arg: None,
+    },
+];
+
+/// Number of stderr lines kept to explain a player failure.
+const STDERR_LINES: usize = 3;
 
 pub struct Player {
-    bin: PathBuf,
+    event_tx: EventSender,
+    bin: Option<PathBuf>,
+    args: Vec<String>,
+    audio_only: bool,
+    quality: Option<u32>,
+    run: usize,
+    mpv: Option<Mpv>,
 }
 
 impl Player {
-    pub fn new() -> Self {
+    pub fn new(config: &PlayerConfig, event_tx: EventSender) -> Self {
+        let (bin, args) = match (&config.command, &config.name) {
+            (Some(command), _) => {
+                let mut tokens = command.split_whitespace().map(String::from);
+                (tokens.next().map(PathBuf::from), tokens.collect())
+            }
+            (None, Some(name)) => (
+                which::which(name).ok(),
+                Self::split(Self::known(Path::new(name)).map_or("{url}", |p| p.args)),
+            ),
+            (None, None) => PLAYERS
+                .iter()
+                .find_map(|p| which::which(p.name).ok().map(|bin| (Some(bin), Self::split(p.args))))
+                .unwrap_or_default(),
+        };
+
         Self {
-            bin: "/usr/bin/celluloid".into(),
+            event_tx,
+            bin,
+            args,
+            audio_only: config.audio_only,
+            quality: config.quality,
+            run: 0,
+            mpv: None,
+        }
+    }
+
+    fn known(bin: &Path) -> Option<&'static KnownPlayer> {
+        let name = bin.file_name().and_then(|n| n.to_str())?;
+        PLAYERS.iter().find(|p| p.name == name)
+    }
+
+    fn split(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    fn is_mpv(bin: &Path) -> bool {
+        bin.file_name().is_some_and(|name| name == "mpv")
+    }
+
+    pub fn quality(&self) -> Option<u32> {
+        self.quality
+    }
+
+    /// Whether the player has arguments to disable video.
+    pub fn supports_audio_only(&self) -> bool {
+        self.bin
+            .as_deref()
+            .and_then(Self::known)
+            .is_some_and(|p| !p.audio_args.is_empty())
+    }
+
+    pub fn is_audio_only(&self) -> bool {
+        self.audio_only && self.supports_audio_only()
+    }
+
+    /// Disable video for the next videos, and for the running one when it is mpv.
+    pub fn set_audio_only(&mut self, audio_only: bool) -> Result<()> {
+        self.audio_only = audio_only;
+        match &self.mpv {
+            Some(mpv) => mpv.set_video(!audio_only),
+            None => Ok(()),
         }
     }
 
-    pub fn play_video(&self, id: &str) {
-        Command::new(&self.bin)
-            .arg(format!("https://www.youtube.com/watch?v={id}"))
-            .stderr(Stdio::null())
+    /// Whether `run` identifies the last started player.
+    pub fn is_current(&self, run: usize) -> bool {
+        self.run == run
+    }
+
+    /// Forget the IPC client once the last started player exited, returns whether `run` was current.
+    pub fn exited(&mut self, run: usize) -> bool {
+        let current = self.is_current(run);
+        if current {
+            self.mpv = None;
+        }
+        current
+    }
+
+    /// IPC client of the last started player, when it is mpv.
+    pub fn mpv(&self) -> Option<&Mpv> {
+        self.mpv.as_ref()
+    }
+
+    /// Start the player in the background, failures are sent as `Event::Error`.
+    ///
+    /// A previous mpv instance is stopped, other players cannot be controlled and are left running.
+    pub fn play_video(&mut self, id: &str, title: &str, start: u64) {
+        self.play(id, title, start, None);
+    }
+
+    /// Start the player on a stream picked in the quality popup.
+    pub fn play_stream(&mut self, id: &str, title: &str, start: u64, stream: &Stream) {
+        self.play(id, title, start, Some(stream));
+    }
+
+    fn play(&mut self, id: &str, title: &str, start: u64, stream: Option<&Stream>) {
+        if let Some(mpv) = self.mpv.take() {
+            let _ = mpv.stop();
+        }
+
+        if let Err(e) = self.spawn(id, title, start, stream) {
+            self.event_tx
+                .send(Event::Error(Report::error(format!("{e:#}"))))
+                .unwrap();
+        }
+    }
+
+    /// youtube-dl format selector for the stream, or for the preferred quality.
+    fn format(&self, stream: Option<&Stream>) -> Option<String> {
+        match (stream, self.quality) {
+            (Some(stream), _) => Some(stream.format()),
+            (None, Some(height)) => Some(format!("bestvideo[height<={height}]+bestaudio/best[height<={height}]")),
+            (None, None) => None,
+        }
+    }
+
+    fn spawn(&mut self, id: &str, title: &str, start: u64, stream: Option<&Stream>) -> Result<()> {
+        let bin = self.bin.as_ref().ok_or(anyhow!("no player found"))?;
+        let known = Self::known(bin);
+
+        // Players without youtube-dl support get the stream itself
+        let url = match (stream, known.and_then(|p| p.format_arg)) {
+            (Some(stream), None) => stream.url.clone(),
+            _ => format!("https://www.youtube.com/watch?v={id}"),
+        };
+
+        self.run += 1;
+        let run = self.run;
+
+        let mut command = Command::new(bin);
+        command.args(self.args.iter().map(|arg| {
+            arg.replace("{url}", &url)
+                .replace("{title}", title)
+                .replace("{start}", &start.to_string())
+        }));
+
+        if self.audio_only {
+            command.args(Self::split(known.map_or("", |p| p.audio_args)));
+        }
+
+        if let (Some(format_arg), Some(format)) = (known.and_then(|p| p.format_arg), self.format(stream)) {
+            command.arg(format!("{format_arg}={format}"));
+        }
+
+        let mpv = Self::is_mpv(bin).then(|| {
+            let socket = dirs::runtime_dir()
+                .unwrap_or_else(env::temp_dir)
+                .join(format!("ytui-{}-{run}.sock", process::id()));
+            command.arg(format!("--input-ipc-server={}", socket.display()));
+            Mpv::new(socket)
+        });
+
+        let child = command
+            .stdin(Stdio::null())
             .stdout(Stdio::null())
-            .spawn();
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("cannot start {}", bin.display()))?;
+
+        let name = bin
+            .file_name()
+            .unwrap_or(bin.as_os_str())
+            .to_string_lossy()
+            .into_owned();
+        let socket = mpv.as_ref().map(|mpv| mpv.socket().clone());
+        let event_tx = self.event_tx.clone();
+        thread::spawn(move || {
+            let result = Self::wait(child, &name);
+            if let Err(e) = &result {
+                let _ = event_tx.send(Event::Error(Report::error(format!("{e:#}"))));
+            }
+            if let Some(socket) = socket {
+                let _ = fs::remove_file(socket);
+            }
+            let _ = event_tx.send(Event::PlayerExit(run, result.is_ok()));
+        });
+
+        if let Some(mpv) = &mpv {
+            mpv.connect(self.event_tx.clone(), run);
+        }
+        self.mpv = mpv;
+
+        Ok(())
+    }
+
+    /// Wait for the player to exit, returning its last stderr lines if it failed.
+    fn wait(mut child: Child, name: &str) -> Result<()> {
+        let mut lines = VecDeque::with_capacity(STDERR_LINES);
+        if let Some(stderr) = child.stderr.take() {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if lines.len() == STDERR_LINES {
+                    lines.pop_front();
+                }
+                lines.push_back(line);
+            }
+        }
+
+        let status = child.wait().with_context(|| format!("cannot wait for {name}"))?;
+        if status.success() {
+            Ok(())
+        } else if lines.is_empty() {
+            Err(anyhow!("{name} exited with {status}"))
+        } else {
+            Err(anyhow!("{name} exited with {status}: {}", Vec::from(lines).join(" | ")))
+        }
     }
 }
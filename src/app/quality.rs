@@ -0,0 +1,123 @@
+use invidious::video::Video;
+use ratatui::widgets::{ListItem, ListState};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum StreamKind {
+    /// Video with audio
+    Muxed,
+    VideoOnly,
+    AudioOnly,
+}
+
+#[derive(Clone, Debug)]
+pub struct Stream {
+    pub url: String,
+    pub itag: String,
+    pub kind: StreamKind,
+    pub label: String,
+    pub height: u32,
+}
+
+impl Stream {
+    /// youtube-dl format selector picking this stream.
+    pub fn format(&self) -> String {
+        match self.kind {
+            StreamKind::VideoOnly => format!("{}+bestaudio/{}", self.itag, self.itag),
+            _ => self.itag.clone(),
+        }
+    }
+}
+
+/// Streams of a video listed in the quality popup.
+pub struct Quality {
+    pub id: String,
+    pub title: String,
+    streams: Vec<Stream>,
+    selection: ListState,
+}
+
+impl Quality {
+    /// List the streams of `video`, selecting the best one within `preferred` height.
+    pub fn new(video: &Video, instance: &str, preferred: Option<u32>) -> Self {
+        // Proxied streams have an URL relative to the instance
+        let absolute = |url: &str| match url.starts_with('/') {
+            true => format!("{instance}{url}"),
+            false => url.to_string(),
+        };
+
+        let muxed = video.format_streams.iter().map(|f| Stream {
+            url: absolute(&f.url),
+            itag: f.itag.clone(),
+            kind: StreamKind::Muxed,
+            label: format!("{} {} {} (video + audio)", f.quality_label, f.container, f.encoding),
+            height: Self::height(&f.resolution),
+        });
+
+        let adaptive = video.adaptive_formats.iter().map(|f| {
+            let audio = f.r#type.starts_with("audio/");
+            Stream {
+                url: absolute(&f.url),
+                itag: f.itag.clone(),
+                kind: if audio {
+                    StreamKind::AudioOnly
+                } else {
+                    StreamKind::VideoOnly
+                },
+                label: if audio {
+                    let kbps = f.bitrate.parse::<u64>().unwrap_or_default() / 1000;
+                    format!("{kbps}kbps {} {} (audio only)", f.container, f.encoding)
+                } else {
+                    format!("{} {} {}", f.quality, f.container, f.encoding)
+                },
+                height: Self::height(&f.resolution),
+            }
+        });
+
+        let streams: Vec<Stream> = muxed.chain(adaptive).collect();
+        let preferred = preferred.unwrap_or(u32::MAX);
+        let selected = streams
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.kind == StreamKind::Muxed && s.height <= preferred)
+            .max_by_key(|(_, s)| s.height)
+            .map(|(i, _)| i)
+            .or((!streams.is_empty()).then_some(0));
+
+        let mut selection = ListState::default();
+        selection.select(selected);
+
+        Self {
+            id: video.id.clone(),
+            title: video.title.clone(),
+            streams,
+            selection,
+        }
+    }
+
+    /// Parse the height of a `WIDTHxHEIGHT` or `HEIGHTp` resolution.
+    fn height(resolution: &str) -> u32 {
+        let height = resolution.rsplit('x').next().unwrap_or_default();
+        height.trim_end_matches('p').parse().unwrap_or_default()
+    }
+
+    pub fn get_list_split<'a>(&'a mut self) -> (Vec<ListItem<'a>>, &'a mut ListState) {
+        (
+            self.streams.iter().map(|s| ListItem::new(s.label.as_str())).collect(),
+            &mut self.selection,
+        )
+    }
+
+    pub fn next_stream(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i + 1);
+        self.selection.select(Some(i.min(self.streams.len().saturating_sub(1))));
+    }
+
+    pub fn previous_stream(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i.saturating_sub(1));
+        self.selection.select(Some(i));
+    }
+
+    pub fn selected_stream(&self) -> Option<&Stream> {
+        self.selection.selected().and_then(|i| self.streams.get(i))
+    }
+}
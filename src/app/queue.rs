@@ -0,0 +1,332 @@
+use super::ui::format_time;
+
+use std::collections::HashSet;
+
+use invidious::hidden::PlaylistItem;
+use invidious::hidden::SearchItem::{self, *};
+use ratatui::widgets::{ListItem, ListState};
+
+#[derive(Clone, Debug)]
+pub struct QueueItem {
+    pub id: String,
+    pub title: String,
+    pub author: String,
+    pub length: u64,
+}
+
+impl QueueItem {
+    pub fn from_search_item(item: &SearchItem) -> Option<Self> {
+        match item {
+            Video {
+                id,
+                title,
+                author,
+                length,
+                ..
+            } => Some(Self {
+                id: id.clone(),
+                title: title.clone(),
+                author: author.clone(),
+                length: *length,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl From<&PlaylistItem> for QueueItem {
+    fn from(item: &PlaylistItem) -> Self {
+        Self {
+            id: item.id.clone(),
+            title: item.title.clone(),
+            author: item.author.clone(),
+            length: item.length.into(),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Default, Debug)]
+pub enum Repeat {
+    #[default]
+    Off,
+    All,
+    One,
+}
+
+#[derive(Default)]
+pub struct Queue {
+    items: Vec<QueueItem>,
+    selection: ListState,
+    current: Option<usize>,
+    /// Whether the playing entry was removed, `current` being then the index of the entry following it
+    removed: bool,
+    shuffle: bool,
+    repeat: Repeat,
+    /// Ids played since the queue was started, to shuffle without repetitions
+    played: HashSet<String>,
+}
+
+impl Queue {
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn push(&mut self, item: QueueItem) {
+        self.items.push(item);
+        if self.selection.selected().is_none() {
+            self.selection.select(Some(0));
+        }
+    }
+
+    pub fn get_list_split<'a>(&'a mut self) -> (Vec<ListItem<'a>>, &'a mut ListState) {
+        (
+            self.items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let marker = if self.current == Some(i) && !self.removed {
+                        "▶ "
+                    } else {
+                        "  "
+                    };
+                    ListItem::new(format!(
+                        "{marker}{} - {} ({})",
+                        item.title,
+                        item.author,
+                        format_time(item.length)
+                    ))
+                })
+                .collect(),
+            &mut self.selection,
+        )
+    }
+
+    pub fn title(&self) -> String {
+        let mut title = format!("Queue ({})", self.items.len());
+        if self.shuffle {
+            title.push_str(" [shuffle]");
+        }
+        match self.repeat {
+            Repeat::Off => {}
+            Repeat::All => title.push_str(" [repeat all]"),
+            Repeat::One => title.push_str(" [repeat one]"),
+        }
+        title
+    }
+
+    pub fn next_selection(&mut self) {
+        let i = match self.selection.selected() {
+            Some(i) => (i + 1).min(self.items.len().saturating_sub(1)),
+            None => 0,
+        };
+        self.selection.select((!self.items.is_empty()).then_some(i));
+    }
+
+    pub fn previous_selection(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i.saturating_sub(1));
+        self.selection.select((!self.items.is_empty()).then_some(i));
+    }
+
+    /// Swap the selected entry with its neighbour, `offset` being -1 or 1.
+    pub fn move_selected(&mut self, offset: isize) {
+        let Some(i) = self.selection.selected() else {
+            return;
+        };
+        let Some(j) = i.checked_add_signed(offset).filter(|j| *j < self.items.len()) else {
+            return;
+        };
+
+        self.items.swap(i, j);
+        self.current = match self.current {
+            Some(c) if c == i => Some(j),
+            Some(c) if c == j => Some(i),
+            c => c,
+        };
+        self.selection.select(Some(j));
+    }
+
+    pub fn remove_selected(&mut self) {
+        let Some(i) = self.selection.selected() else {
+            return;
+        };
+
+        self.items.remove(i);
+        self.current = match self.current {
+            // Keep following the queue from the entry taking the place of the playing one
+            Some(c) if c == i => {
+                self.removed = true;
+                Some(c)
+            }
+            Some(c) if c > i => Some(c - 1),
+            c => c,
+        };
+        self.selection.select(match self.items.len() {
+            0 => None,
+            len => Some(i.min(len - 1)),
+        });
+    }
+
+    pub fn toggle_shuffle(&mut self) {
+        self.shuffle = !self.shuffle;
+    }
+
+    pub fn cycle_repeat(&mut self) {
+        self.repeat = match self.repeat {
+            Repeat::Off => Repeat::All,
+            Repeat::All => Repeat::One,
+            Repeat::One => Repeat::Off,
+        };
+    }
+
+    /// Start playing the selected entry.
+    pub fn play_selected(&mut self) -> Option<&QueueItem> {
+        self.play_at(self.selection.selected()?)
+    }
+
+    /// Start playing the `i`-th entry.
+    pub fn play_at(&mut self, i: usize) -> Option<&QueueItem> {
+        self.played.clear();
+        self.removed = false;
+        self.current = Some(i).filter(|i| *i < self.items.len());
+        self.selection.select(self.current.or(self.selection.selected()));
+        self.current.map(|i| {
+            self.played.insert(self.items[i].id.clone());
+            &self.items[i]
+        })
+    }
+
+    /// Stop following the queue, the player exiting will not start the next entry.
+    pub fn stop(&mut self) {
+        self.current = None;
+        self.removed = false;
+    }
+
+    /// Advance to the entry to play after the current one, if any.
+    pub fn next(&mut self) -> Option<&QueueItem> {
+        let current = self.current?;
+        let len = self.items.len();
+        let removed = std::mem::take(&mut self.removed);
+        if len == 0 {
+            self.current = None;
+            return None;
+        }
+
+        self.current = match (self.repeat, self.shuffle, removed) {
+            // The entry following the removed one already took its place
+            (Repeat::Off, false, true) => Some(current).filter(|i| *i < len),
+            (_, false, true) => Some(current % len),
+            (Repeat::One, _, false) => Some(current),
+            (_, true, _) => {
+                let mut left: Vec<usize> = (0..len).filter(|i| !self.played.contains(&self.items[*i].id)).collect();
+                if left.is_empty() && self.repeat == Repeat::All {
+                    self.played.clear();
+                    left = (0..len).filter(|i| *i != current || len == 1).collect();
+                }
+                (!left.is_empty()).then(|| left[fastrand::usize(..left.len())])
+            }
+            (Repeat::All, false, false) => Some((current + 1) % len),
+            (Repeat::Off, false, false) => Some(current + 1).filter(|i| *i < len),
+        };
+
+        self.current.map(|i| {
+            self.played.insert(self.items[i].id.clone());
+            &self.items[i]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(ids: &[&str]) -> Queue {
+        let mut queue = Queue::default();
+        for id in ids {
+            queue.push(QueueItem {
+                id: id.to_string(),
+                title: id.to_string(),
+                author: String::new(),
+                length: 0,
+            });
+        }
+        queue
+    }
+
+    fn ids(queue: &mut Queue, count: usize) -> Vec<Option<String>> {
+        (0..count).map(|_| queue.next().map(|item| item.id.clone())).collect()
+    }
+
+    #[test]
+    fn shuffle_plays_every_entry_once() {
+        let mut queue = queue(&["a", "b", "c", "d", "e"]);
+        queue.toggle_shuffle();
+        queue.play_at(2);
+
+        let mut played: Vec<String> = ids(&mut queue, 4).into_iter().map(Option::unwrap).collect();
+        played.push("c".to_string());
+        played.sort();
+        assert_eq!(played, ["a", "b", "c", "d", "e"]);
+        assert_eq!(queue.next().map(|item| item.id.as_str()), None);
+    }
+
+    #[test]
+    fn shuffle_starts_over_with_repeat_all() {
+        let mut queue = queue(&["a", "b", "c"]);
+        queue.toggle_shuffle();
+        queue.cycle_repeat();
+        queue.play_at(0);
+
+        let played = ids(&mut queue, 8);
+        assert!(played.iter().all(Option::is_some));
+        assert!(played.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn repeat_all_wraps_around() {
+        let mut queue = queue(&["a", "b", "c"]);
+        queue.cycle_repeat();
+        queue.play_at(1);
+
+        let expected = ["c", "a", "b", "c"].map(|id| Some(id.to_string()));
+        assert_eq!(ids(&mut queue, 4), expected);
+    }
+
+    #[test]
+    fn repeat_one_plays_the_same_entry() {
+        let mut queue = queue(&["a", "b", "c"]);
+        queue.cycle_repeat();
+        queue.cycle_repeat();
+        queue.play_at(1);
+
+        assert_eq!(
+            ids(&mut queue, 3),
+            [Some("b".to_string()), Some("b".to_string()), Some("b".to_string())]
+        );
+    }
+
+    #[test]
+    fn removing_the_playing_entry_continues_with_the_next() {
+        let mut queue = queue(&["a", "b", "c"]);
+        queue.play_at(1);
+        queue.selection.select(Some(1));
+        queue.remove_selected();
+
+        assert_eq!(queue.next().map(|item| item.id.as_str()), Some("c"));
+        assert_eq!(queue.next().map(|item| item.id.as_str()), None);
+    }
+
+    #[test]
+    fn removing_the_last_playing_entry_wraps_with_repeat_all() {
+        let mut queue = queue(&["a", "b"]);
+        queue.cycle_repeat();
+        queue.play_at(1);
+        queue.selection.select(Some(1));
+        queue.remove_selected();
+
+        assert_eq!(queue.next().map(|item| item.id.as_str()), Some("a"));
+    }
+}
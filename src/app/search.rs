@@ -1,42 +1,330 @@
+use super::channel::{Sort, Tab, POST_TYPE};
+use super::ui::{format_time, STYLE_AUTHOR};
+
 use image::DynamicImage;
 use invidious::hidden::SearchItem::{self, *};
+use invidious::hidden::{PlaylistItem, VideoShort, VideoThumbnail};
+use invidious::universal;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{ListItem, ListState};
 
+/// Number of items left below the selection before the next page is fetched.
+const PAGE_PRELOAD: usize = 5;
+
+/// Source of the items of the results list.
+#[derive(Clone, Debug)]
+pub enum Query {
+    /// Searched text, and the URL parameters of the search without the page
+    Search { input: String, params: String },
+    /// Playlist id
+    Playlist(String),
+    /// Tab of a channel, paginated by continuation
+    Channel { id: String, tab: Tab, sort: Sort },
+    /// Searched text within a channel
+    ChannelSearch { id: String, input: String },
+    /// Videos recommended alongside a video, given by its id
+    Related(String),
+}
+
+impl Query {
+    /// What was searched, for the messages of the results pane.
+    pub fn label(&self) -> String {
+        match self {
+            Query::Search { input, .. } => format!("\"{input}\""),
+            Query::Playlist(_) => String::from("this playlist"),
+            Query::Channel { .. } => String::from("this channel"),
+            Query::ChannelSearch { input, .. } => format!("\"{input}\" in this channel"),
+            Query::Related(_) => String::from("videos related to this one"),
+        }
+    }
+
+    /// Whether the next page is given by a token of the previous one rather than its number.
+    fn is_continued(&self) -> bool {
+        matches!(self, Query::Channel { .. })
+    }
+}
+
+/// Metadata of a playlist shown above its videos.
+#[derive(Clone, Debug)]
+pub struct PlaylistInfo {
+    pub title: String,
+    pub author: String,
+    pub description: String,
+    pub video_count: u32,
+    pub views: u64,
+    /// UNIX timestamp of the last update
+    pub updated: u64,
+}
+
+impl From<&universal::Playlist> for PlaylistInfo {
+    fn from(playlist: &universal::Playlist) -> Self {
+        Self {
+            title: playlist.title.clone(),
+            author: playlist.author.clone(),
+            description: playlist.description.clone(),
+            video_count: playlist.video_count,
+            views: playlist.views,
+            updated: playlist.updated,
+        }
+    }
+}
+
+/// Page of results, with the token of the next one for the queries paginated by continuation.
+pub struct Page {
+    pub items: Vec<SearchItem>,
+    pub continuation: Option<String>,
+    /// Metadata of the listed playlist
+    pub playlist: Option<PlaylistInfo>,
+}
+
+impl From<Vec<SearchItem>> for Page {
+    fn from(items: Vec<SearchItem>) -> Self {
+        Self {
+            items,
+            continuation: None,
+            playlist: None,
+        }
+    }
+}
+
+/// Progress of the request for the first page of results.
+#[derive(Clone, Debug, Default)]
+pub enum Status {
+    /// Nothing searched yet
+    #[default]
+    Idle,
+    Loading,
+    Loaded,
+    Empty,
+    Failed(String),
+}
+
+/// Percent-encode a URL parameter value.
+pub fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// `SearchItem::Video` of a video listed without its statistics, as in playlists and recommendations.
+fn short_video(
+    id: &str,
+    title: &str,
+    author: &str,
+    author_id: &str,
+    author_url: &str,
+    length: u64,
+    thumbnails: &[VideoThumbnail],
+) -> SearchItem {
+    Video {
+        title: title.to_string(),
+        id: id.to_string(),
+        author: author.to_string(),
+        author_id: author_id.to_string(),
+        author_url: author_url.to_string(),
+        length,
+        thumbnails: thumbnails.to_vec(),
+        description: String::new(),
+        description_html: String::new(),
+        views: 0,
+        published: 0,
+        published_text: String::new(),
+        live: false,
+        paid: false,
+        premium: false,
+    }
+}
+
+/// Convert a playlist entry to the `SearchItem::Video` it would be in search results.
+pub fn playlist_video(item: &PlaylistItem) -> SearchItem {
+    short_video(
+        &item.id,
+        &item.title,
+        &item.author,
+        &item.author_id,
+        &item.author_url,
+        item.length.into(),
+        &item.thumbnails,
+    )
+}
+
+/// Convert a recommended video to the `SearchItem::Video` it would be in search results.
+pub fn related_video(video: &VideoShort) -> SearchItem {
+    short_video(
+        &video.id,
+        &video.title,
+        &video.author,
+        "",
+        "",
+        video.length.into(),
+        &video.thumbnails,
+    )
+}
+
 #[derive(Default)]
 pub struct Search {
     items: Vec<(SearchItem, Option<DynamicImage>)>,
     selection: ListState,
+    query: Option<Query>,
+    /// Last page fetched
+    page: u32,
+    /// Token of the next page
+    continuation: Option<String>,
+    playlist: Option<PlaylistInfo>,
+    /// Fetching the next page
+    loading: bool,
+    exhausted: bool,
+    status: Status,
 }
 
 impl From<Vec<SearchItem>> for Search {
     fn from(items: Vec<SearchItem>) -> Self {
         Self {
             items: items.into_iter().map(|i| (i, None)).collect(),
-            selection: ListState::default(),
+            ..Default::default()
         }
     }
 }
 
 impl Search {
+    /// First page of results of `query`.
+    pub fn new(query: Query, page: Page) -> Self {
+        Self {
+            page: 1,
+            exhausted: Self::is_last(&query, &page, 0),
+            status: match page.items.is_empty() {
+                true => Status::Empty,
+                false => Status::Loaded,
+            },
+            query: Some(query),
+            continuation: page.continuation,
+            playlist: page.playlist,
+            ..page.items.into()
+        }
+    }
+
+    /// Whether `page` ends the results of `query`, following `loaded` results.
+    fn is_last(query: &Query, page: &Page, loaded: usize) -> bool {
+        let complete = page
+            .playlist
+            .as_ref()
+            .is_some_and(|playlist| loaded + page.items.len() >= playlist.video_count as usize);
+        page.items.is_empty() || complete || (query.is_continued() && page.continuation.is_none())
+    }
+
+    /// Empty results waiting for the first page of `query`.
+    pub fn loading(query: Query) -> Self {
+        Self {
+            query: Some(query),
+            status: Status::Loading,
+            ..Default::default()
+        }
+    }
+
+    pub fn query(&self) -> Option<&Query> {
+        self.query.as_ref()
+    }
+
+    /// Metadata of the playlist whose videos are listed.
+    pub fn playlist(&self) -> Option<&PlaylistInfo> {
+        self.playlist.as_ref()
+    }
+
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    pub fn set_failed(&mut self, error: String) {
+        self.status = Status::Failed(error);
+    }
+
+    /// Whether a page of results is being fetched.
+    pub fn is_loading(&self) -> bool {
+        self.loading || matches!(self.status, Status::Loading)
+    }
+
+    /// What was searched, for the messages of the results pane.
+    pub fn label(&self) -> String {
+        self.query.as_ref().map(Query::label).unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Query, number and token of the page to fetch, if the selection is close enough to the end.
+    pub fn next_page(&self) -> Option<(Query, u32, Option<String>)> {
+        if self.loading || self.exhausted {
+            return None;
+        }
+
+        let selected = self.selection.selected()?;
+        if selected + PAGE_PRELOAD < self.items.len() {
+            return None;
+        }
+
+        self.query
+            .clone()
+            .map(|query| (query, self.page + 1, self.continuation.clone()))
+    }
+
+    pub fn set_loading(&mut self) {
+        self.loading = true;
+    }
+
+    /// Forget the failed fetch of the next page so that it can be retried.
+    pub fn page_failed(&mut self) {
+        self.loading = false;
+    }
+
+    /// Append the next page of results, an empty page marks the end of the results.
+    pub fn append(&mut self, page: Page) {
+        self.loading = false;
+        self.page += 1;
+        self.exhausted = self.query.as_ref().map_or(page.items.is_empty(), |query| {
+            Self::is_last(query, &page, self.items.len())
+        });
+        self.continuation = page.continuation;
+        self.items.extend(page.items.into_iter().map(|i| (i, None)));
+    }
+
     pub fn get_list_split<'a>(&'a mut self) -> (Vec<ListItem<'a>>, &'a mut ListState) {
-        (
-            self.items
-                .iter()
-                .map(|item| {
-                    ListItem::new(match &item.0 {
-                        Video { title, .. } => title.as_str(),
-                        Playlist { title, .. } => title.as_str(),
-                        Channel { name, .. } => name.as_str(),
-                        Unknown(_) => "Error",
-                    })
+        let mut items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|item| {
+                ListItem::new(match &item.0 {
+                    Video { title, length, .. } if *length > 0 => Line::from(vec![
+                        Span::raw(title.as_str()),
+                        Span::styled(format!(" {}", format_time(*length)), STYLE_AUTHOR),
+                    ]),
+                    Video { title, .. } => Line::from(title.as_str()),
+                    Playlist { title, .. } => Line::from(title.as_str()),
+                    Channel { name, .. } => Line::from(name.as_str()),
+                    Unknown(post) if post.r#type == POST_TYPE => Line::from(
+                        post.description
+                            .as_deref()
+                            .and_then(|d| d.lines().next())
+                            .unwrap_or_default(),
+                    ),
+                    Unknown(_) => Line::from("Error"),
                 })
-                .collect(),
-            &mut self.selection,
-        )
+            })
+            .collect();
+
+        if self.loading {
+            items.push(ListItem::new("Loading more...").style(STYLE_AUTHOR));
+        }
+
+        (items, &mut self.selection)
     }
 
     pub fn next_video(&mut self) {
-        if self.items.len() != 0 {
+        if !self.items.is_empty() {
             let i = match self.selection.selected() {
                 Some(i) if i == self.items.len() - 1 => self.items.len() - 1,
                 Some(mut i) => {
@@ -53,9 +341,9 @@ impl Search {
     }
 
     pub fn previous_video(&mut self) {
-        if self.items.len() != 0 {
+        if !self.items.is_empty() {
             let i = match self.selection.selected() {
-                Some(i) if i == 0 => 0,
+                Some(0) => 0,
                 Some(mut i) => {
                     i -= 1;
                     i %= self.items.len();
@@ -69,11 +357,36 @@ impl Search {
         }
     }
 
+    pub fn items(&self) -> impl Iterator<Item = &SearchItem> {
+        self.items.iter().map(|(item, _)| item)
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selection.selected()
+    }
+
     pub fn selected_item(&self) -> Option<&(SearchItem, Option<DynamicImage>)> {
-        self.selection.selected().and_then(|i| Some(&self.items[i]))
+        self.selection.selected().map(|i| &self.items[i])
+    }
+
+    /// Copy of the results without their thumbnails, to keep in the navigation history.
+    pub fn snapshot(&self) -> Self {
+        Self {
+            items: self.items.iter().map(|(item, _)| (item.clone(), None)).collect(),
+            selection: self.selection.clone(),
+            query: self.query.clone(),
+            page: self.page,
+            continuation: self.continuation.clone(),
+            playlist: self.playlist.clone(),
+            loading: self.loading,
+            exhausted: self.exhausted,
+            status: self.status.clone(),
+        }
     }
 
     pub fn set_thumbnail(&mut self, i: usize, image: DynamicImage) {
-        self.items[i].1 = Some(image);
+        if let Some(item) = self.items.get_mut(i) {
+            item.1 = Some(image);
+        }
     }
 }
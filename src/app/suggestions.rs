@@ -0,0 +1,72 @@
+use invidious::PublicItems;
+use ratatui::widgets::{ListItem, ListState};
+use serde::{Deserialize, Serialize};
+
+/// Response of the `/api/v1/search/suggestions` endpoint, missing from the invidious crate.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SuggestionsResponse {
+    pub query: String,
+    pub suggestions: Vec<String>,
+}
+
+impl PublicItems for SuggestionsResponse {
+    fn url(server: &str, args: String) -> String {
+        format!("{server}/api/v1/search/suggestions{args}")
+    }
+}
+
+/// Autocomplete dropdown of the search input.
+#[derive(Default)]
+pub struct Suggestions {
+    items: Vec<String>,
+    selection: ListState,
+}
+
+impl Suggestions {
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn set(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.selection.select(None);
+    }
+
+    pub fn clear(&mut self) {
+        self.set(vec![]);
+    }
+
+    pub fn next_suggestion(&mut self) {
+        let i = self.selection.selected().map_or(0, |i| i + 1);
+        self.selection.select(Some(i.min(self.items.len().saturating_sub(1))));
+    }
+
+    /// Select the previous suggestion, going back to the input from the first one.
+    pub fn previous_suggestion(&mut self) {
+        self.selection
+            .select(self.selection.selected().and_then(|i| i.checked_sub(1)));
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.selection
+            .selected()
+            .and_then(|i| self.items.get(i))
+            .map(String::as_str)
+    }
+
+    /// Selected suggestion, or the first one if none is selected.
+    pub fn completion(&self) -> Option<&str> {
+        self.selected().or(self.items.first().map(String::as_str))
+    }
+
+    pub fn get_list_split<'a>(&'a mut self) -> (Vec<ListItem<'a>>, &'a mut ListState) {
+        (
+            self.items.iter().map(|s| ListItem::new(s.as_str())).collect(),
+            &mut self.selection,
+        )
+    }
+}
@@ -0,0 +1,247 @@
+use super::cache::{Cache, Meta};
+use super::errors::Report;
+use super::instances::Instances;
+use crate::config::ThumbnailsConfig;
+use crate::Event;
+use crate::EventSender;
+
+use std::error::Error;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+use image::io::Reader as ImageReader;
+use invidious::hidden::SearchItem::{self, *};
+use reqwest::header::{HeaderMap, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use tokio::runtime::Runtime;
+use tokio::select;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Default)]
+struct Pending {
+    /// Index in the results and URL of the thumbnails left to fetch
+    items: Vec<(usize, String)>,
+    /// Index of the selected result, fetched first along with its neighbours
+    selected: usize,
+    /// Number of workers still looking for thumbnails to fetch
+    workers: usize,
+}
+
+impl Pending {
+    /// Take the thumbnail closest to the selection, those below it being the most likely to be shown next.
+    fn take(&mut self) -> Option<(usize, String)> {
+        let selected = self.selected;
+        let distance = |i: usize| match i >= selected {
+            true => i - selected,
+            false => (selected - i) * 2,
+        };
+
+        let (position, _) = self.items.iter().enumerate().min_by_key(|(_, (i, _))| distance(*i))?;
+        Some(self.items.swap_remove(position))
+    }
+}
+
+/// Fetches the thumbnails of the results with a bounded number of concurrent requests.
+pub struct Thumbnails {
+    event_tx: EventSender,
+    instances: Arc<Instances>,
+    cache: Arc<Cache>,
+    concurrency: usize,
+    pending: Arc<Mutex<Pending>>,
+    token: CancellationToken,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Thumbnails {
+    pub fn new(config: &ThumbnailsConfig, event_tx: EventSender, instances: Arc<Instances>) -> Self {
+        Self {
+            event_tx,
+            instances,
+            cache: Arc::new(Cache::new(config)),
+            concurrency: config.concurrency.max(1),
+            pending: Arc::default(),
+            token: CancellationToken::new(),
+            workers: vec![],
+        }
+    }
+
+    fn url(item: &SearchItem) -> Option<String> {
+        match item {
+            Video { thumbnails, .. } => thumbnails.first().map(|t| t.url.clone()),
+            Channel { thumbnails, .. } => thumbnails.first().map(|t| t.url.clone()),
+            Playlist { thumbnail, .. } if !thumbnail.is_empty() => Some(thumbnail.clone()),
+            _ => None,
+        }
+    }
+
+    /// Queue the thumbnails of results starting at index `offset`, starting workers as needed.
+    pub fn fetch<'a>(
+        &mut self,
+        rt: &Runtime,
+        generation: usize,
+        items: impl Iterator<Item = &'a SearchItem>,
+        offset: usize,
+    ) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.items.extend(
+            items
+                .enumerate()
+                .filter_map(|(i, item)| Self::url(item).map(|url| (offset + i, url))),
+        );
+
+        let count = self
+            .concurrency
+            .saturating_sub(pending.workers)
+            .min(pending.items.len());
+        pending.workers += count;
+        drop(pending);
+
+        self.workers.retain(|worker| !worker.is_finished());
+        for _ in 0..count {
+            self.workers.push(rt.spawn(Self::run_worker(
+                self.event_tx.clone(),
+                self.instances.clone(),
+                self.cache.clone(),
+                self.pending.clone(),
+                self.token.clone(),
+                generation,
+            )));
+        }
+    }
+
+    /// Fetch the thumbnails around the selected result first.
+    pub fn select(&self, i: usize) {
+        self.pending.lock().unwrap().selected = i;
+    }
+
+    /// Cancel the fetching of the thumbnails of the previous results.
+    pub fn stop(&mut self, rt: &Runtime) {
+        self.token.cancel();
+        for mut worker in self.workers.drain(..) {
+            let _ = rt.block_on(&mut worker);
+        }
+
+        self.token = CancellationToken::new();
+        *self.pending.lock().unwrap() = Pending::default();
+    }
+
+    async fn run_worker(
+        event_tx: EventSender,
+        instances: Arc<Instances>,
+        cache: Arc<Cache>,
+        pending: Arc<Mutex<Pending>>,
+        token: CancellationToken,
+        generation: usize,
+    ) {
+        select! {
+            _ = Self::work(event_tx, &instances, &cache, &pending, generation) => {},
+            _ = token.cancelled() => {},
+        };
+    }
+
+    async fn work(
+        event_tx: EventSender,
+        instances: &Instances,
+        cache: &Arc<Cache>,
+        pending: &Mutex<Pending>,
+        generation: usize,
+    ) {
+        loop {
+            let next = {
+                let mut pending = pending.lock().unwrap();
+                let next = pending.take();
+                if next.is_none() {
+                    pending.workers -= 1;
+                }
+                next
+            };
+
+            match next {
+                Some((i, url)) => {
+                    let result = Self::fetch_thumbnail(event_tx.clone(), instances, cache, generation, i, &url).await;
+                    if let Err(e) = result {
+                        let report = Report::warning(format!("cannot load a thumbnail: {e}")).quiet();
+                        let _ = event_tx.send(Event::Error(report));
+                    }
+                }
+                None => return,
+            }
+        }
+    }
+
+    async fn fetch_thumbnail(
+        event_tx: EventSender,
+        instances: &Instances,
+        cache: &Arc<Cache>,
+        generation: usize,
+        i: usize,
+        url: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        // Relative URLs are cached as such, to be shared by every instance
+        let cached = cache.get(url).await;
+
+        let bytes = match cached {
+            Some(entry) if entry.is_fresh() => entry.content,
+            cached => match Self::download(instances, cache, url, cached.as_ref().map(|e| &e.meta)).await {
+                Ok(Some(bytes)) => bytes,
+                // Not modified, or offline
+                Ok(None) | Err(_) if cached.is_some() => cached.unwrap().content,
+                Ok(None) => return Err("empty response".into()),
+                Err(e) => return Err(e),
+            },
+        };
+
+        let thumbnail = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?.decode()?;
+
+        event_tx.send(Event::Thumbnail(generation, i, thumbnail)).unwrap();
+
+        Ok(())
+    }
+
+    /// Download a thumbnail from the active instance if `url` is relative and cache it,
+    /// `None` if the cached one is still valid.
+    async fn download(
+        instances: &Instances,
+        cache: &Arc<Cache>,
+        url: &str,
+        cached: Option<&Meta>,
+    ) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        let mut headers = HeaderMap::new();
+        if let Some(meta) = cached {
+            if let Some(etag) = meta.etag.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(IF_NONE_MATCH, etag);
+            }
+            if let Some(date) = meta
+                .last_modified
+                .as_deref()
+                .and_then(|v| HeaderValue::from_str(v).ok())
+            {
+                headers.insert(IF_MODIFIED_SINCE, date);
+            }
+        }
+
+        let response = instances.http().get_with(&instances.absolute(url), headers).await?;
+        if let (StatusCode::NOT_MODIFIED, Some(cached)) = (response.status(), cached) {
+            if let Some(meta) = Cache::meta(url, response.headers()) {
+                cache
+                    .refresh(&Meta {
+                        etag: meta.etag.or(cached.etag.clone()),
+                        last_modified: meta.last_modified.or(cached.last_modified.clone()),
+                        ..meta
+                    })
+                    .await;
+            }
+            return Ok(None);
+        }
+
+        let response = response.error_for_status()?;
+        let meta = Cache::meta(url, response.headers());
+        let bytes = response.bytes().await?.to_vec();
+        if let Some(meta) = meta {
+            cache.put(meta, bytes.clone()).await;
+        }
+
+        Ok(Some(bytes))
+    }
+}
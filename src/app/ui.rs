@@ -1,5 +1,72 @@
+use ratatui::layout::Rect;
 use ratatui::style::{Color, Modifier, Style};
 
+/// Rectangle of the given percentage of `rect`, centered in it, for popups.
+pub fn centered_rect(percent_x: u16, percent_y: u16, rect: Rect) -> Rect {
+    let width = rect.width * percent_x / 100;
+    let height = rect.height * percent_y / 100;
+    Rect::new(
+        rect.x + (rect.width - width) / 2,
+        rect.y + (rect.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Frames of the loading animation.
+const SPINNER: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Frame of the loading animation at the given tick.
+pub fn spinner(tick: usize) -> &'static str {
+    SPINNER[tick % SPINNER.len()]
+}
+
+/// Format a duration in seconds as `h:mm:ss` or `m:ss`.
+pub fn format_time(seconds: u64) -> String {
+    let (h, m, s) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+/// Format a count with thousands separators.
+pub fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(c);
+    }
+    formatted
+}
+
+/// Format a UNIX timestamp as a `yyyy-mm-dd` UTC date.
+pub fn format_date(timestamp: u64) -> String {
+    // Civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{y}-{m:02}-{d:02}")
+}
+
+/// Text drawn at a position of the terminal, to be made a hyperlink after the frame is drawn.
+pub struct Link {
+    pub x: u16,
+    pub y: u16,
+    pub text: String,
+    pub url: String,
+}
+
 pub const STYLE_DEFAULT: Style = Style {
     fg: Some(Color::Reset),
     bg: Some(Color::Reset),
@@ -34,3 +101,24 @@ pub const STYLE_HIGHLIGHT_ITEM: Style = Style {
     add_modifier: Modifier::empty(),
     sub_modifier: Modifier::empty(),
 };
+
+pub const STYLE_WARNING: Style = Style {
+    fg: Some(Color::Yellow),
+    bg: Some(Color::Reset),
+    add_modifier: Modifier::empty(),
+    sub_modifier: Modifier::empty(),
+};
+
+pub const STYLE_ERROR: Style = Style {
+    fg: Some(Color::Red),
+    bg: Some(Color::Reset),
+    add_modifier: Modifier::BOLD,
+    sub_modifier: Modifier::empty(),
+};
+
+pub const STYLE_LINK: Style = Style {
+    fg: Some(Color::Blue),
+    bg: Some(Color::Reset),
+    add_modifier: Modifier::UNDERLINED,
+    sub_modifier: Modifier::empty(),
+};
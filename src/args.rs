@@ -1,6 +1,63 @@
+use crate::app::filters::{FEATURES, PARAMS};
+
+use std::path::PathBuf;
+
+use clap::builder::PossibleValuesParser;
 use clap::Parser;
 
 /// YouTube in the terminal
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
-pub struct Args {}
+pub struct Args {
+    /// Player to use (mpv, vlc, celluloid...), auto-detected by default
+    #[arg(short, long)]
+    pub player: Option<String>,
+
+    /// Player command template, placeholders: {url}, {title}, {start}
+    #[arg(long, value_name = "TEMPLATE")]
+    pub player_command: Option<String>,
+
+    /// Only play the audio track
+    #[arg(short, long)]
+    pub audio_only: bool,
+
+    /// Preferred maximum video height, e.g. 720
+    #[arg(long, value_name = "HEIGHT")]
+    pub quality: Option<u32>,
+
+    /// Directory the videos are downloaded to
+    #[arg(long, value_name = "DIR")]
+    pub download_dir: Option<PathBuf>,
+
+    /// Type of search results
+    #[arg(long = "type", value_name = "TYPE", value_parser = PossibleValuesParser::new(PARAMS[0].2))]
+    pub kind: Option<String>,
+
+    /// Duration of the videos
+    #[arg(long, value_parser = PossibleValuesParser::new(PARAMS[1].2))]
+    pub duration: Option<String>,
+
+    /// Upload date of the videos
+    #[arg(long, value_parser = PossibleValuesParser::new(PARAMS[2].2))]
+    pub date: Option<String>,
+
+    /// Order of the search results
+    #[arg(long, value_parser = PossibleValuesParser::new(PARAMS[3].2))]
+    pub sort_by: Option<String>,
+
+    /// Required features of the videos, comma separated
+    #[arg(long, value_delimiter = ',', value_parser = PossibleValuesParser::new(FEATURES))]
+    pub features: Vec<String>,
+
+    /// Invidious instance to use, can be repeated to fail over to the next ones
+    #[arg(short, long, value_name = "URL")]
+    pub instance: Vec<String>,
+
+    /// Do not remember the searched queries
+    #[arg(long)]
+    pub no_history: bool,
+
+    /// Delete the search history before starting
+    #[arg(long)]
+    pub clear_history: bool,
+}
@@ -0,0 +1,216 @@
+use crate::args::Args;
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use toml_edit::Document;
+
+/// Settings read from `$XDG_CONFIG_HOME/ytui/config.toml`
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct Config {
+    pub player: PlayerConfig,
+    pub downloads: DownloadsConfig,
+    pub search: SearchConfig,
+    pub history: HistoryConfig,
+    pub network: NetworkConfig,
+    pub thumbnails: ThumbnailsConfig,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct PlayerConfig {
+    /// Name or path of a known player (mpv, vlc, celluloid...)
+    pub name: Option<String>,
+    /// Full command template, takes precedence over `name`
+    pub command: Option<String>,
+    /// Only play the audio track
+    pub audio_only: bool,
+    /// Preferred maximum video height, e.g. 720
+    pub quality: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct DownloadsConfig {
+    /// Directory the videos are saved to, the XDG download directory by default
+    pub dir: Option<PathBuf>,
+    /// yt-dlp compatible downloader
+    pub command: Option<String>,
+}
+
+/// Default search filters, see `app::filters` for the accepted values
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct SearchConfig {
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub duration: Option<String>,
+    pub date: Option<String>,
+    pub sort_by: Option<String>,
+    pub features: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Remember the searched queries across sessions
+    pub enabled: bool,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Invidious instances by order of preference, e.g. "https://yewtu.be"
+    pub instances: Vec<String>,
+    /// Seconds allowed to connect to a server
+    pub connect_timeout: u64,
+    /// Seconds allowed for a whole request, response included
+    pub timeout: u64,
+    /// Retries of a request failing with a server error or rate limited
+    pub retries: u32,
+    /// User-Agent header, `ytui/<version>` by default
+    pub user_agent: Option<String>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            instances: vec![],
+            connect_timeout: 5,
+            timeout: 20,
+            retries: 3,
+            user_agent: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ThumbnailsConfig {
+    /// Maximum number of thumbnails downloaded at the same time
+    pub concurrency: usize,
+    /// Size limit of the thumbnail cache in MiB, 0 disables it
+    pub cache_size: u64,
+}
+
+impl Default for ThumbnailsConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            cache_size: 100,
+        }
+    }
+}
+
+impl Config {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("ytui").join("config.toml"))
+    }
+
+    /// Load the config file, falling back to defaults if it does not exist.
+    pub fn load() -> Result<Self> {
+        let path = match Self::path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(Self::default()),
+        };
+
+        let content = fs::read_to_string(&path).with_context(|| format!("cannot read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("cannot parse {}", path.display()))
+    }
+
+    /// Change the `key` setting of `table` in the config file, leaving the rest of the file as it is.
+    pub fn set(table: &str, key: &str, value: impl Into<toml_edit::Value>) -> Result<()> {
+        let path = Self::path().context("no config directory")?;
+        let content = match path.exists() {
+            true => fs::read_to_string(&path).with_context(|| format!("cannot read {}", path.display()))?,
+            false => String::new(),
+        };
+
+        let content =
+            Self::edit(&content, table, key, value).with_context(|| format!("cannot parse {}", path.display()))?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+        }
+        fs::write(&path, content).with_context(|| format!("cannot write {}", path.display()))
+    }
+
+    fn edit(content: &str, table: &str, key: &str, value: impl Into<toml_edit::Value>) -> Result<String> {
+        let mut document: Document = content.parse()?;
+        document[table][key] = toml_edit::value(value);
+        Ok(document.to_string())
+    }
+
+    /// Override the settings given on the command line.
+    pub fn with_args(mut self, args: Args) -> Self {
+        if args.player.is_some() {
+            self.player.name = args.player;
+            self.player.command = None;
+        }
+        if args.player_command.is_some() {
+            self.player.command = args.player_command;
+        }
+        if args.audio_only {
+            self.player.audio_only = true;
+        }
+        if args.quality.is_some() {
+            self.player.quality = args.quality;
+        }
+        if args.download_dir.is_some() {
+            self.downloads.dir = args.download_dir;
+        }
+        if args.kind.is_some() {
+            self.search.kind = args.kind;
+        }
+        if args.duration.is_some() {
+            self.search.duration = args.duration;
+        }
+        if args.date.is_some() {
+            self.search.date = args.date;
+        }
+        if args.sort_by.is_some() {
+            self.search.sort_by = args.sort_by;
+        }
+        if !args.features.is_empty() {
+            self.search.features = args.features;
+        }
+        if args.no_history {
+            self.history.enabled = false;
+        }
+        if !args.instance.is_empty() {
+            self.network.instances = args.instance;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_keeps_the_rest_of_the_file() {
+        let content = "# Player settings\n[player]\nname = \"mpv\"  # preferred\naudio_only = false\n\n[history]\nenabled = false\n";
+        let edited = Config::edit(content, "player", "audio_only", true).unwrap();
+
+        assert_eq!(edited, content.replace("audio_only = false", "audio_only = true"));
+    }
+
+    #[test]
+    fn edit_adds_a_missing_table() {
+        let edited = Config::edit("# Empty\n", "player", "audio_only", true).unwrap();
+        let config: Config = toml::from_str(&edited).unwrap();
+
+        assert!(edited.contains("# Empty\n"));
+        assert!(config.player.audio_only);
+    }
+}
@@ -1,4 +1,9 @@
-use crate::app::search::Search;
+use crate::app::comments::{CommentsQuery, CommentsResponse};
+use crate::app::downloads::DownloadUpdate;
+use crate::app::errors::Report;
+use crate::app::mpv::Playback;
+use crate::app::search::{Page, Search};
+use crate::app::PlaylistAction;
 
 use crossterm::event::{KeyEvent, MouseEvent};
 use image::DynamicImage;
@@ -7,11 +12,27 @@ pub enum Event {
     FocusGained,
     FocusLost,
     Key(KeyEvent),
+    #[allow(dead_code)]
     Mouse(MouseEvent),
+    #[allow(dead_code)]
     Paste(String),
+    #[allow(dead_code)]
     Resize(u16, u16),
-    Fetch(Search),
-    Thumbnail(usize, DynamicImage),
+    /// First page of a search, tagged with the generation of the search
+    Fetch(usize, Search),
+    /// Periodic event driving the loading animations
+    Tick,
+    Page(usize, Page),
+    /// Generation of the search, index of the result and its thumbnail
+    Thumbnail(usize, usize, DynamicImage),
+    Playlist(PlaylistAction, invidious::universal::Playlist),
+    Video(Box<invidious::video::Video>),
+    Error(Report),
+    PlayerExit(usize, bool),
+    Playback(usize, Playback),
+    Download(usize, DownloadUpdate),
+    Suggestions(String, Vec<String>),
+    Comments(CommentsQuery, CommentsResponse),
 }
 
 impl From<crossterm::event::Event> for Event {
@@ -1,23 +1,37 @@
 mod app;
 mod args;
+mod config;
 mod event;
 
+use app::errors::Report;
+use app::history::History;
 use app::App;
 use args::Args;
+use config::Config;
 use event::Event;
 
-use std::io;
+use std::io::{self, Write};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::thread;
+use std::time::Duration;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::Result;
 use clap::Parser;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{backend::CrosstermBackend, Terminal};
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    Terminal,
+};
+
+/// Interval between two frames of the loading animations.
+const TICK_RATE: Duration = Duration::from_millis(100);
+
+/// Delay before reading the terminal again after a failure.
+const READ_RETRY_DELAY: Duration = Duration::from_secs(1);
 
 type EventSender = Sender<Event>;
 type EventReceiver = Receiver<Event>;
@@ -25,6 +39,18 @@ type EventChannel = (EventSender, EventReceiver);
 
 fn main() -> Result<()> {
     let args = Args::parse();
+    if args.clear_history {
+        History::clear()?;
+    }
+    let config = Config::load()?.with_args(args);
+
+    // channel to handle events
+    let (tx, rx): EventChannel = channel();
+    let term_tx = tx.clone();
+    let tick_tx = tx.clone();
+
+    // create app before the terminal is taken over, to report errors normally
+    let mut app = App::new(tx, config)?;
 
     // setup terminal
     enable_raw_mode()?;
@@ -33,40 +59,75 @@ fn main() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // channel to handle events
-    let (tx, rx): EventChannel = channel();
-    let term_tx = tx.clone();
-
     // thread to handle terminal events
     thread::spawn(move || loop {
-        let ret: Result<()> = match crossterm::event::read() {
-            Ok(event) => term_tx.send(event.into()).context("tx error"),
-            Err(e) => Err(anyhow!(e)),
+        let event = match crossterm::event::read() {
+            Ok(event) => event.into(),
+            Err(e) => {
+                // Do not flood the app if the terminal keeps failing
+                thread::sleep(READ_RETRY_DELAY);
+                Event::Error(Report::error(format!("cannot read the terminal: {e}")))
+            }
         };
+
+        // The receiver is gone once the app exits
+        if term_tx.send(event).is_err() {
+            break;
+        }
+    });
+
+    // thread to animate the ui
+    thread::spawn(move || loop {
+        thread::sleep(TICK_RATE);
+        if tick_tx.send(Event::Tick).is_err() {
+            break;
+        }
     });
 
-    // create app and run it
-    let mut app = App::new(tx);
+    // run app, the terminal is restored even if the app failed
+    let result = run(&mut terminal, &mut app, rx);
 
+    // restore terminal, every step is tried before reporting the first failure
+    let raw = disable_raw_mode();
+    let screen = execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture);
+    let cursor = terminal.show_cursor();
+
+    result?;
+    raw?;
+    screen?;
+    cursor?;
+    Ok(())
+}
+
+fn run<B: Backend + Write>(terminal: &mut Terminal<B>, app: &mut App, rx: EventReceiver) -> Result<()> {
+    let mut redraw = true;
     while app.is_running() {
-        // redraw the ui on event
-        terminal.draw(|f| app.ui(f));
+        // redraw the ui on event, ticks only when something is animated
+        if redraw {
+            terminal.draw(|f| app.ui(f))?;
+            app.write_links(terminal.backend_mut())?;
+        }
+        redraw = true;
 
-        match rx.recv() {
-            Ok(event) => match event {
+        if let Ok(event) = rx.recv() {
+            match event {
+                Event::Tick => redraw = app.handle_tick_event(),
                 Event::Key(key) => app.handle_key_event(key),
-                Event::Fetch(search) => app.handle_fetch_event(search),
-                Event::Thumbnail(i, thumbnail) => app.handle_thumbnail_event(i, thumbnail),
+                Event::Fetch(generation, search) => app.handle_fetch_event(generation, search),
+                Event::Page(generation, page) => app.handle_page_event(generation, page),
+                Event::Thumbnail(generation, i, thumbnail) => app.handle_thumbnail_event(generation, i, thumbnail),
+                Event::Playlist(action, playlist) => app.handle_playlist_event(action, playlist),
+                Event::Video(video) => app.handle_video_event(video),
+                Event::Error(report) => app.handle_error_event(report),
+                Event::PlayerExit(run, success) => app.handle_player_exit_event(run, success),
+                Event::Playback(run, playback) => app.handle_playback_event(run, playback),
+                Event::Download(key, update) => app.handle_download_event(key, update),
+                Event::Suggestions(query, suggestions) => app.handle_suggestions_event(query, suggestions),
+                Event::Comments(query, response) => app.handle_comments_event(query, response),
                 _ => {}
-            },
-            Err(e) => {}
+            }
         }
     }
 
-    // restore terminal
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
-    terminal.show_cursor()?;
-
     Ok(())
 }